@@ -12,13 +12,21 @@ pub trait Day<'a> {
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1);
     fn solve_part2(input: Self::Input2) -> Self::Output2;
 
+    fn solve_with_args(_raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        Err(format!(
+            "day{:02} doesn't take extra arguments, got {:?}",
+            Self::INDEX,
+            args
+        ))
+    }
+
     fn solve_and_print(raw_input: &'a str)
     where
         Self::Output1: fmt::Display,
         Self::Output2: fmt::Display,
     {
         let time0 = Instant::now();
-        let parsed_input = Self::parse(&raw_input);
+        let parsed_input = Self::parse(raw_input);
         let time1 = Instant::now();
         let (parsed_input, part1_answer) = Self::solve_part1(parsed_input);
         let time2 = Instant::now();
@@ -1,12 +1,192 @@
-use crate::day::Day;
+use crate::{day::Day, util::Flags};
+use std::collections::{BTreeMap, HashMap};
+
+const TARGET: u64 = 2020;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KSum {
+    pub indices: Vec<usize>,
+    pub product: u64,
+}
+
+fn sorted_entries(entries: &[u32]) -> Vec<(u64, usize)> {
+    let mut sorted: Vec<(u64, usize)> = entries
+        .iter()
+        .enumerate()
+        .map(|(idx, &entry)| (entry as u64, idx))
+        .collect();
+    sorted.sort_unstable();
+    sorted
+}
+
+// k distinct entries (by index) summing to target, with their indices in increasing order
+fn find_k_sum(entries: &[u32], k: usize, target: u64) -> Option<Vec<usize>> {
+    let mut found = None;
+    search(
+        &sorted_entries(entries),
+        k,
+        target,
+        &mut Vec::with_capacity(k),
+        &mut |chosen| {
+            found = Some(chosen.to_vec());
+            true
+        },
+    );
+    found.map(|mut indices: Vec<usize>| {
+        indices.sort_unstable();
+        indices
+    })
+}
+
+// every such choice of indices, in lexicographic order
+fn all_k_sums(entries: &[u32], k: usize, target: u64) -> Vec<Vec<usize>> {
+    let mut found = Vec::new();
+    search(
+        &sorted_entries(entries),
+        k,
+        target,
+        &mut Vec::with_capacity(k),
+        &mut |chosen| {
+            let mut indices = chosen.to_vec();
+            indices.sort_unstable();
+            found.push(indices);
+            false
+        },
+    );
+    found.sort_unstable();
+    found
+}
+
+// calls visit with the indices of every choice of k entries from sorted summing to target, stopping
+// as soon as visit returns true
+fn search<F>(
+    sorted: &[(u64, usize)],
+    k: usize,
+    target: u64,
+    chosen: &mut Vec<usize>,
+    visit: &mut F,
+) -> bool
+where
+    F: FnMut(&[usize]) -> bool,
+{
+    let mut visit_with = |chosen: &mut Vec<usize>, extra: &[usize]| {
+        chosen.extend(extra);
+        let stop = visit(chosen);
+        chosen.truncate(chosen.len() - extra.len());
+        stop
+    };
+    match k {
+        0 => target == 0 && visit_with(chosen, &[]),
+        1 => {
+            let start = sorted.partition_point(|pair| pair.0 < target);
+            let end = sorted.partition_point(|pair| pair.0 <= target);
+            sorted[start..end]
+                .iter()
+                .any(|&(_, idx)| visit_with(chosen, &[idx]))
+        }
+        2 => {
+            let (mut lo, mut hi) = (0, sorted.len());
+            while lo + 1 < hi {
+                let (low, high) = (sorted[lo].0, sorted[hi - 1].0);
+                if low + high < target {
+                    lo += 1;
+                } else if low + high > target {
+                    hi -= 1;
+                } else if low == high {
+                    // everything left is equal, so any two of them will do
+                    for a in lo..hi {
+                        for b in a + 1..hi {
+                            if visit_with(chosen, &[sorted[a].1, sorted[b].1]) {
+                                return true;
+                            }
+                        }
+                    }
+                    break;
+                } else {
+                    let low_end = lo + sorted[lo..hi].partition_point(|pair| pair.0 == low);
+                    let high_start = lo + sorted[lo..hi].partition_point(|pair| pair.0 < high);
+                    for a in lo..low_end {
+                        for b in high_start..hi {
+                            if visit_with(chosen, &[sorted[a].1, sorted[b].1]) {
+                                return true;
+                            }
+                        }
+                    }
+                    lo = low_end;
+                    hi = high_start;
+                }
+            }
+            false
+        }
+        _ => {
+            for (pos, &(value, idx)) in sorted.iter().enumerate() {
+                // the rest are at least as big, so every later choice overshoots too
+                if value.saturating_mul(k as u64) > target {
+                    break;
+                }
+                chosen.push(idx);
+                if search(&sorted[pos + 1..], k - 1, target - value, chosen, visit) {
+                    return true;
+                }
+                chosen.pop();
+            }
+            false
+        }
+    }
+}
+
+// the number of choices of k entries summing to target, or None if it doesn't fit in a u128. Equal
+// entries are handled together, taking c of the m copies in m choose c ways, and only sums that
+// are actually reachable are tracked
+fn count_k_sums(entries: &[u32], k: usize, target: u64) -> Option<u128> {
+    let mut multiplicities = BTreeMap::new();
+    for &entry in entries {
+        *multiplicities.entry(entry as u64).or_insert(0u64) += 1;
+    }
+    // ways[j][sum] counts the choices of j entries with that sum
+    let mut ways: Vec<HashMap<u64, u128>> = vec![HashMap::new(); k + 1];
+    ways[0].insert(0, 1);
+    for (&value, &count) in multiplicities.iter() {
+        let mut next = ways.clone();
+        for size in 0..k {
+            for (&sum, &prev_ways) in ways[size].iter() {
+                let mut copy_ways = 1u128;
+                for copies in 1..=count.min((k - size) as u64) {
+                    let new_sum = sum.saturating_add(copies * value);
+                    if new_sum > target {
+                        break;
+                    }
+                    copy_ways =
+                        copy_ways.checked_mul((count - copies + 1) as u128)? / copies as u128;
+                    let total = next[size + copies as usize].entry(new_sum).or_insert(0);
+                    *total = total.checked_add(prev_ways.checked_mul(copy_ways)?)?;
+                }
+            }
+        }
+        ways = next;
+    }
+    Some(ways[k].get(&target).copied().unwrap_or(0))
+}
+
+fn solve_k_sum(entries: &[u32], k: usize, target: u64) -> Result<KSum, String> {
+    let indices = find_k_sum(entries, k, target)
+        .ok_or_else(|| format!("no {} entries sum to {}", k, target))?;
+    let product = indices
+        .iter()
+        .try_fold(1u64, |product, &idx| {
+            product.checked_mul(entries[idx] as u64)
+        })
+        .ok_or_else(|| format!("product of entries at {:?} overflows", indices))?;
+    Ok(KSum { indices, product })
+}
 
 pub struct Day01 {}
 
 impl<'a> Day<'a> for Day01 {
     type Input1 = Vec<u32>;
     type Input2 = Vec<u32>;
-    type Output1 = u32;
-    type Output2 = u32;
+    type Output1 = u64;
+    type Output2 = u64;
 
     const INDEX: usize = 1;
 
@@ -22,31 +202,61 @@ impl<'a> Day<'a> for Day01 {
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
-        let mut answer = None;
-        for (idx1, num1) in input.iter().enumerate() {
-            for num2 in input[0..=idx1].iter() {
-                if num1 + num2 == 2020 {
-                    answer = Some(num1 * num2);
-                    break;
-                }
-            }
-        }
-        (input, answer.unwrap())
+        let answer = solve_k_sum(&input, 2, TARGET).unwrap_or_else(|err| panic!("{}", err));
+        (input, answer.product)
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
-        for (idx1, num1) in input.iter().enumerate() {
-            for (idx2, num2) in input[0..=idx1].iter().enumerate() {
-                if num1 + num2 > 2020 {
-                    continue;
-                }
-                for num3 in input[0..=idx2].iter() {
-                    if num1 + num2 + num3 == 2020 {
-                        return num1 * num2 * num3;
-                    }
-                }
+        let answer = solve_k_sum(&input, 3, TARGET).unwrap_or_else(|err| panic!("{}", err));
+        answer.product
+    }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(args, &["--target", "--k", "--all", "--count"])?;
+        let target = flags.get("--target")?.unwrap_or(TARGET);
+        let k = flags.get("--k")?.unwrap_or(2);
+        let entries = Self::parse(raw_input);
+        if flags.has("--count") {
+            let count = count_k_sums(&entries, k, target)
+                .ok_or_else(|| "number of combinations overflows".to_string())?;
+            println!();
+            println!(
+                "day{:02} ({} entries summing to {}):",
+                Self::INDEX,
+                k,
+                target
+            );
+            println!("  combinations: {}", count);
+            return Ok(());
+        }
+        if flags.has("--all") {
+            let combinations = all_k_sums(&entries, k, target);
+            println!();
+            println!(
+                "day{:02} ({} entries summing to {}):",
+                Self::INDEX,
+                k,
+                target
+            );
+            for indices in combinations.iter() {
+                let chosen: Vec<_> = indices.iter().map(|&idx| entries[idx]).collect();
+                println!("  {:?} {:?}", indices, chosen);
             }
+            println!("  combinations: {}", combinations.len());
+            return Ok(());
         }
-        panic!("no solution found");
+        let answer = solve_k_sum(&entries, k, target)?;
+        let chosen: Vec<_> = answer.indices.iter().map(|&idx| entries[idx]).collect();
+        println!();
+        println!(
+            "day{:02} ({} entries summing to {}):",
+            Self::INDEX,
+            k,
+            target
+        );
+        println!("  indices: {:?}", answer.indices);
+        println!("  entries: {:?}", chosen);
+        println!("  product: {}", answer.product);
+        Ok(())
     }
 }
@@ -1,66 +1,267 @@
-use crate::day::Day;
-use itertools::Itertools;
+use crate::{
+    day::Day,
+    util::{
+        parse::{
+            self, integer, lines, literal, pair, rest, take_while1, terminated, Input, Parser,
+        },
+        pattern::CharClass,
+        Flags,
+    },
+};
 
-type Password<'a> = &'a [u8];
+// positions count unicode scalar values, so multibyte letters take up a single position
+type Password<'a> = &'a str;
 
-pub struct Policy {
-    min: usize,
-    max: usize,
-    letter: u8,
+// the two numbers and class before the colon, which each policy interprets in its own way
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    first: usize,
+    second: usize,
+    class: CharClass,
 }
 
-impl Policy {
-    fn validate_sled(&self, password: &[u8]) -> bool {
-        let count = bytecount::count(password, self.letter);
-        self.min <= count && count <= self.max
+pub trait Policy {
+    fn name(&self) -> &'static str;
+
+    // the reason the password fails, if it does
+    fn check(&self, rule: &Rule, password: Password) -> Result<(), String>;
+}
+
+// the class appears between first and second times, inclusive
+pub struct CountRange;
+
+impl Policy for CountRange {
+    fn name(&self) -> &'static str {
+        "count"
+    }
+
+    fn check(&self, rule: &Rule, password: Password) -> Result<(), String> {
+        let count = password
+            .chars()
+            .filter(|&letter| rule.class.matches(letter))
+            .count();
+        if rule.first <= count && count <= rule.second {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} appears {} times, not {}-{}",
+                rule.class, count, rule.first, rule.second
+            ))
+        }
+    }
+}
+
+// checks the class against the letters at the 1-based positions first and second, failing if
+// either is outside the password
+fn positions_matching(rule: &Rule, password: Password) -> Result<[bool; 2], String> {
+    let mut matching = [false; 2];
+    for (matches, position) in matching.iter_mut().zip([rule.first, rule.second]) {
+        let letter = position
+            .checked_sub(1)
+            .and_then(|idx| password.chars().nth(idx))
+            .ok_or_else(|| {
+                format!(
+                    "position {} is outside the {} letter password",
+                    position,
+                    password.chars().count()
+                )
+            })?;
+        *matches = rule.class.matches(letter);
+    }
+    Ok(matching)
+}
+
+// exactly one of the two positions matches the class
+pub struct PositionalXor;
+
+impl Policy for PositionalXor {
+    fn name(&self) -> &'static str {
+        "xor"
     }
 
-    fn validate_toboggan(&self, password: &[u8]) -> bool {
-        (password[self.min - 1] == self.letter) ^ (password[self.max - 1] == self.letter)
+    fn check(&self, rule: &Rule, password: Password) -> Result<(), String> {
+        match positions_matching(rule, password)? {
+            [true, false] | [false, true] => Ok(()),
+            [true, true] => Err(format!(
+                "{} is at both positions {} and {}",
+                rule.class, rule.first, rule.second
+            )),
+            [false, false] => Err(format!(
+                "{} is at neither position {} nor {}",
+                rule.class, rule.first, rule.second
+            )),
+        }
+    }
+}
+
+// both positions match the class
+pub struct PositionalAnd;
+
+impl Policy for PositionalAnd {
+    fn name(&self) -> &'static str {
+        "and"
+    }
+
+    fn check(&self, rule: &Rule, password: Password) -> Result<(), String> {
+        let [at_first, at_second] = positions_matching(rule, password)?;
+        let missing: Vec<_> = [(at_first, rule.first), (at_second, rule.second)]
+            .iter()
+            .filter(|pair| !pair.0)
+            .map(|pair| pair.1.to_string())
+            .collect();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} isn't at position {}",
+                rule.class,
+                missing.join(" or ")
+            ))
+        }
+    }
+}
+
+pub struct PolicyEngine {
+    policies: Vec<Box<dyn Policy>>,
+}
+
+impl PolicyEngine {
+    pub fn new() -> Self {
+        let mut engine = Self {
+            policies: Vec::new(),
+        };
+        engine.register(Box::new(CountRange));
+        engine.register(Box::new(PositionalXor));
+        engine.register(Box::new(PositionalAnd));
+        engine
+    }
+
+    // replaces any policy already registered under the same name
+    pub fn register(&mut self, policy: Box<dyn Policy>) {
+        self.policies.retain(|other| other.name() != policy.name());
+        self.policies.push(policy);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn Policy, String> {
+        self.policies
+            .iter()
+            .find(|policy| policy.name() == name)
+            .map(|policy| policy.as_ref())
+            .ok_or_else(|| {
+                let names: Vec<_> = self.policies.iter().map(|policy| policy.name()).collect();
+                format!("unknown policy {:?}, expected one of {:?}", name, names)
+            })
+    }
+
+    pub fn count_valid(&self, name: &str, entries: &[(Rule, Password)]) -> Result<usize, String> {
+        let policy = self.get(name)?;
+        Ok(entries
+            .iter()
+            .filter(|pair| policy.check(&pair.0, pair.1).is_ok())
+            .count())
+    }
+}
+
+fn char_class<'a>() -> impl Parser<'a, CharClass> {
+    |input: Input<'a>| {
+        let (pattern, rest) = take_while1(|c| c != ':', "a character class").parse(input)?;
+        match pattern.parse() {
+            Ok(class) => Ok((class, rest)),
+            Err(err) => Err(input.error(format!("a character class ({})", err))),
+        }
     }
 }
 
 pub struct Day02 {}
 
 impl<'a> Day<'a> for Day02 {
-    type Input1 = Vec<(Policy, Password<'a>)>;
-    type Input2 = Vec<(Policy, Password<'a>)>;
+    type Input1 = Vec<(Rule, Password<'a>)>;
+    type Input2 = Vec<(Rule, Password<'a>)>;
     type Output1 = usize;
     type Output2 = usize;
 
     const INDEX: usize = 2;
 
     fn parse(input: &'a str) -> Self::Input1 {
-        input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| {
-                let (min, max, letter, password) = line
-                    .split(&['-', ' ', ':'][..])
-                    .filter(|s| !s.is_empty())
-                    .next_tuple()
-                    .expect("failed to parse line");
-                let min = min.parse::<usize>().expect("failed to parse min");
-                let max = max.parse::<usize>().expect("failed to parse max");
-                assert!(letter.len() == 1, "expected single letter");
-                let letter = letter.as_bytes()[0];
-                (Policy { min, max, letter }, password.as_bytes())
+        let rule = pair(
+            pair(
+                terminated(integer(), literal("-")),
+                terminated(integer(), literal(" ")),
+            ),
+            terminated(char_class(), literal(": ")),
+        );
+        let line = pair(rule, rest());
+        parse::parse_all(lines(line), input)
+            .unwrap_or_else(|err| panic!("failed to parse passwords: {}", err))
+            .into_iter()
+            .map(|(((first, second), class), password)| {
+                (
+                    Rule {
+                        first,
+                        second,
+                        class,
+                    },
+                    password,
+                )
             })
             .collect()
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
-        let count = input
-            .iter()
-            .filter(|pair| pair.0.validate_sled(pair.1))
-            .count();
+        let count = PolicyEngine::new().count_valid("count", &input).unwrap();
         (input, count)
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
-        input
-            .iter()
-            .filter(|pair| pair.0.validate_toboggan(pair.1))
-            .count()
+        PolicyEngine::new().count_valid("xor", &input).unwrap()
+    }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(args, &["--policies", "--report"])?;
+        let names: String = flags
+            .get("--policies")?
+            .unwrap_or_else(|| "count,xor".to_string());
+        let engine = PolicyEngine::new();
+        let policies = names
+            .split(',')
+            .map(|name| engine.get(name.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let entries = Self::parse(raw_input);
+        println!();
+        println!("day{:02} ({}):", Self::INDEX, names);
+        let mut valid_counts = vec![0; policies.len()];
+        for (rule, password) in entries.iter() {
+            let results: Vec<_> = policies
+                .iter()
+                .map(|policy| policy.check(rule, password))
+                .collect();
+            for (count, result) in valid_counts.iter_mut().zip(results.iter()) {
+                if result.is_ok() {
+                    *count += 1;
+                }
+            }
+            if flags.has("--report") {
+                let verdicts: Vec<_> = policies
+                    .iter()
+                    .zip(results)
+                    .map(|(policy, result)| match result {
+                        Ok(()) => format!("{} passes", policy.name()),
+                        Err(reason) => format!("{} fails ({})", policy.name(), reason),
+                    })
+                    .collect();
+                println!(
+                    "  {}-{} {}: {}: {}",
+                    rule.first,
+                    rule.second,
+                    rule.class,
+                    password,
+                    verdicts.join(", ")
+                );
+            }
+        }
+        for (policy, count) in policies.iter().zip(valid_counts) {
+            println!("  {}: {} of {} valid", policy.name(), count, entries.len());
+        }
+        Ok(())
     }
 }
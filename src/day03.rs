@@ -1,34 +1,137 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{Flags, Grid2D},
+};
+use std::{collections::HashSet, fmt, fs, str::FromStr};
+
+// a step of right columns and down rows, so 2/2 only visits every other row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slope {
+    right: usize,
+    down: usize,
+}
+
+impl Slope {
+    fn new(right: usize, down: usize) -> Option<Self> {
+        if down == 0 {
+            return None;
+        }
+        Some(Self { right, down })
+    }
+}
+
+impl FromStr for Slope {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (right, down) = text.split_once('/').unwrap_or((text, "1"));
+        let parse = |part: &str| {
+            part.trim()
+                .parse()
+                .map_err(|_| format!("invalid slope \"{}\", expected e.g. 3 or 1/3", text))
+        };
+        Self::new(parse(right)?, parse(down)?).ok_or_else(|| {
+            format!(
+                "invalid slope \"{}\", can't move right without going down",
+                text
+            )
+        })
+    }
+}
+
+impl fmt::Display for Slope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.right, self.down)
+    }
+}
+
+const PART1_SLOPE: Slope = Slope { right: 3, down: 1 };
+const PART2_SLOPES: [Slope; 5] = [
+    Slope { right: 1, down: 1 },
+    Slope { right: 3, down: 1 },
+    Slope { right: 5, down: 1 },
+    Slope { right: 7, down: 1 },
+    Slope { right: 1, down: 2 },
+];
 
 pub struct Grid {
-    rows: Vec<Vec<bool>>,
+    trees: Grid2D<bool>,
 }
 
 impl Grid {
     fn width(&self) -> usize {
-        self.rows[0].len()
+        self.trees.width()
     }
 
     fn height(&self) -> usize {
-        self.rows.len()
+        self.trees.height()
     }
 
+    // the pattern repeats to the right
     fn tree_at(&self, x: usize, y: usize) -> bool {
-        self.rows[y][x % self.width()]
+        self.trees
+            .get_wrapping(x as isize, y as isize)
+            .is_some_and(|&tree| tree)
     }
 
-    fn count_trees(&self, x_slope: usize, y_slope: usize) -> usize {
-        let mut x = 0;
-        let mut y = 0;
-        let mut trees = 0;
-        while y < self.height() {
-            if self.tree_at(x, y) {
-                trees += 1;
+    // the cells visited from the top left until falling off the bottom, without wrapping x
+    fn path(&self, slope: Slope) -> impl Iterator<Item = (usize, usize)> {
+        (0..self.height())
+            .step_by(slope.down)
+            .enumerate()
+            .map(move |(step, y)| (step * slope.right, y))
+    }
+
+    fn count_trees(&self, slope: Slope) -> usize {
+        self.path(slope)
+            .filter(|&(x, y)| self.tree_at(x, y))
+            .count()
+    }
+
+    // the map with the path marked, O where it hits a tree and X where it misses, repeating the
+    // pattern to the right until the path ends
+    fn render(&self, slope: Slope, color: bool) -> String {
+        let path: HashSet<_> = self.path(slope).collect();
+        let furthest = path.iter().map(|cell| cell.0).max().unwrap_or(0);
+        let tiles = furthest.checked_div(self.width()).unwrap_or(0) + 1;
+        let mut text = String::new();
+        for y in 0..self.height() {
+            for x in 0..tiles * self.width() {
+                let tree = self.tree_at(x, y);
+                let cell = match (path.contains(&(x, y)), tree, color) {
+                    (false, true, _) => "#",
+                    (false, false, _) => ".",
+                    (true, true, false) => "O",
+                    (true, false, false) => "X",
+                    (true, true, true) => "\x1b[1;31mO\x1b[0m",
+                    (true, false, true) => "\x1b[1;32mX\x1b[0m",
+                };
+                text.push_str(cell);
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    // the slope with the fewest (or most) trees, trying every down up to max_down with every
+    // right < width, as larger ones land on the same cells of the repeating pattern; ties go to
+    // the smallest down, then the smallest right
+    fn best_slope(&self, max_down: usize, most: bool) -> Option<(Slope, usize)> {
+        let candidates = (1..=max_down)
+            .flat_map(|down| (0..self.width()).map(move |right| Slope { right, down }));
+        let mut best: Option<(Slope, usize)> = None;
+        for slope in candidates {
+            let trees = self.count_trees(slope);
+            let better = match best {
+                None => true,
+                Some((_, best_trees)) if most => trees > best_trees,
+                Some((_, best_trees)) => trees < best_trees,
+            };
+            if better {
+                best = Some((slope, trees));
             }
-            x += x_slope;
-            y += y_slope;
         }
-        trees
+        best
     }
 }
 
@@ -43,25 +146,106 @@ impl<'a> Day<'a> for Day03 {
     const INDEX: usize = 3;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
+        let trees = Grid2D::parse(raw_input, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        });
         Grid {
-            rows: raw_input
-                .lines()
-                .filter(|line| !line.is_empty())
-                .map(|line| line.chars().map(|c| c == '#').collect())
-                .collect(),
+            trees: trees.expect("failed to parse map"),
         }
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
-        let count = input.count_trees(3, 1);
+        let count = input.count_trees(PART1_SLOPE);
         (input, count)
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
-        input.count_trees(1, 1)
-            * input.count_trees(3, 1)
-            * input.count_trees(5, 1)
-            * input.count_trees(7, 1)
-            * input.count_trees(1, 2)
+        PART2_SLOPES
+            .iter()
+            .map(|&slope| input.count_trees(slope))
+            .product()
+    }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(
+            args,
+            &[
+                "--slopes",
+                "--search",
+                "--max-down",
+                "--render",
+                "--color",
+                "--output",
+            ],
+        )?;
+        let grid = Self::parse(raw_input);
+        println!();
+        if let Some(goal) = flags.get::<String>("--search")? {
+            let most = match goal.as_str() {
+                "fewest" => false,
+                "most" => true,
+                _ => {
+                    return Err(format!(
+                        "invalid search \"{}\", expected fewest or most",
+                        goal
+                    ))
+                }
+            };
+            let max_down = flags
+                .get("--max-down")?
+                .unwrap_or(grid.height().saturating_sub(1));
+            let (slope, trees) = grid
+                .best_slope(max_down, most)
+                .ok_or_else(|| "no slopes to search, --max-down must be positive".to_string())?;
+            println!(
+                "day{:02} ({} trees, going at most {} down):",
+                Self::INDEX,
+                goal,
+                max_down
+            );
+            println!("  slope: {}", slope);
+            println!("  trees: {}", trees);
+            let cells: Vec<_> = grid
+                .path(slope)
+                .map(|(x, y)| {
+                    let mark = if grid.tree_at(x, y) { "#" } else { "" };
+                    format!("({}, {}){}", x, y, mark)
+                })
+                .collect();
+            println!("  visited (# marks trees): {}", cells.join(" "));
+            return Ok(());
+        }
+        let slopes = match flags.get::<String>("--slopes")? {
+            Some(list) => list
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<Slope>, _>>()?,
+            None => PART2_SLOPES.to_vec(),
+        };
+        if flags.has("--render") {
+            let color = flags.has("--color");
+            let maps: Vec<_> = slopes
+                .iter()
+                .map(|&slope| format!("{}:\n{}", slope, grid.render(slope, color)))
+                .collect();
+            let text = maps.join("\n");
+            match flags.get::<String>("--output")? {
+                Some(path) => fs::write(&path, text)
+                    .map_err(|err| format!("failed to write \"{}\": {}", path, err))?,
+                None => print!("{}", text),
+            }
+            return Ok(());
+        }
+        println!("day{:02}:", Self::INDEX);
+        let mut product = 1usize;
+        for &slope in slopes.iter() {
+            let trees = grid.count_trees(slope);
+            println!("  {}: {}", slope, trees);
+            product = product.saturating_mul(trees);
+        }
+        println!("  product: {}", product);
+        Ok(())
     }
 }
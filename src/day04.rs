@@ -1,120 +1,454 @@
-use crate::day::Day;
-use std::collections::HashMap;
+use crate::{
+    day::Day,
+    util::{
+        parse::{
+            self, blocks, literal, map, optional, pair, take_while1, terminated, Input, Parser,
+        },
+        pattern::Pattern,
+        Flags,
+    },
+};
+use std::{cmp::Reverse, collections::HashMap, fmt, fs, ops::RangeInclusive, str::FromStr};
 
 pub struct Day04 {}
 
-fn validate_number(text: &str, min: usize, max: usize) -> bool {
-    text.parse::<usize>()
-        .map(|n| min <= n && n <= max)
-        .unwrap_or(false)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Validator {
+    // present but never checked
+    Ignored,
+    Range(u64, u64),
+    // a number followed by one of the units, each with its own range
+    Units(Vec<(String, u64, u64)>),
+    Pattern(Pattern),
+    OneOf(Vec<String>),
 }
 
-fn split_last2(text: &str) -> (&str, &str) {
-    text.split_at((text.len() - 2).max(0))
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    Missing,
+    NotANumber(String),
+    OutOfRange { value: String, range: String },
+    NoUnit(String),
+    NoMatch { value: String, pattern: String },
+    NotAllowed(String),
+    Duplicated(Vec<String>),
 }
 
-pub struct KeyedPassport<'a> {
-    birth_year: &'a str,
-    issue_year: &'a str,
-    expiration_year: &'a str,
-    height: &'a str,
-    hair_color: &'a str,
-    eye_color: &'a str,
-    passport_id: &'a str,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    Valid,
+    Ignored,
+    // not in the schema, which is allowed
+    Unknown,
+    Invalid(Problem),
 }
 
-impl<'a> KeyedPassport<'a> {
-    fn valid(&self) -> bool {
-        let birth_year_valid = validate_number(self.birth_year, 1920, 2002);
-        let issue_year_valid = validate_number(self.issue_year, 2010, 2020);
-        let expiration_year_valid = validate_number(self.expiration_year, 2020, 2030);
-        let height_valid = match split_last2(self.height) {
-            (number, "cm") => validate_number(number, 150, 193),
-            (number, "in") => validate_number(number, 59, 76),
-            _ => false,
-        };
-        let hair_color_valid = self.hair_color.len() == 7
-            && self.hair_color.starts_with('#')
-            && self
-                .hair_color
-                .chars()
-                .skip(1)
-                .all(|c| c.is_ascii_hexdigit());
-        let eye_color_valid = match self.eye_color {
-            "amb" | "blu" | "brn" | "gry" | "grn" | "hzl" | "oth" => true,
-            _ => false,
-        };
-        let passport_id_valid =
-            self.passport_id.len() == 9 && self.passport_id.chars().all(|c| c.is_ascii_digit());
+impl Problem {
+    // the kind of problem without the value, for grouping
+    fn summary(&self) -> &'static str {
+        match self {
+            Self::Missing => "missing",
+            Self::NotANumber(_) => "not a number",
+            Self::OutOfRange { .. } => "out of range",
+            Self::NoUnit(_) => "no unit",
+            Self::NoMatch { .. } => "doesn't match pattern",
+            Self::NotAllowed(_) => "not in allowed set",
+            Self::Duplicated(_) => "duplicated",
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => write!(f, "missing"),
+            Self::NotANumber(value) => write!(f, "{} is not a number", value),
+            Self::OutOfRange { value, range } => write!(f, "{} is outside {}", value, range),
+            Self::NoUnit(value) => write!(f, "{} has no unit", value),
+            Self::NoMatch { value, pattern } => write!(f, "{} doesn't match {}", value, pattern),
+            Self::NotAllowed(value) => write!(f, "{} not in allowed set", value),
+            Self::Duplicated(values) => write!(f, "given more than once: {}", values.join(", ")),
+        }
+    }
+}
+
+// the number at the start of value, which is followed by unit
+fn check_number(value: &str, unit: &str, min: u64, max: u64) -> Result<(), Problem> {
+    let number = &value[..value.len() - unit.len()];
+    match number.parse::<u64>() {
+        Ok(n) if min <= n && n <= max => Ok(()),
+        Ok(_) => Err(Problem::OutOfRange {
+            value: value.to_string(),
+            range: format!("{}-{}{}", min, max, unit),
+        }),
+        Err(_) => Err(Problem::NotANumber(value.to_string())),
+    }
+}
+
+impl Validator {
+    fn check(&self, value: &str) -> Result<(), Problem> {
+        match self {
+            Self::Ignored => Ok(()),
+            Self::Range(min, max) => check_number(value, "", *min, *max),
+            Self::Units(units) => {
+                let (unit, min, max) = units
+                    .iter()
+                    .find(|(unit, _, _)| value.ends_with(unit.as_str()))
+                    .ok_or_else(|| Problem::NoUnit(value.to_string()))?;
+                check_number(value, unit, *min, *max)
+            }
+            Self::Pattern(pattern) => {
+                if pattern.matches(value) {
+                    Ok(())
+                } else {
+                    Err(Problem::NoMatch {
+                        value: value.to_string(),
+                        pattern: pattern.to_string(),
+                    })
+                }
+            }
+            Self::OneOf(options) => {
+                if options.iter().any(|option| option == value) {
+                    Ok(())
+                } else {
+                    Err(Problem::NotAllowed(value.to_string()))
+                }
+            }
+        }
+    }
+}
 
-        birth_year_valid
-            && issue_year_valid
-            && expiration_year_valid
-            && height_valid
-            && hair_color_valid
-            && eye_color_valid
-            && passport_id_valid
+fn parse_range(text: &str) -> Result<(u64, u64), String> {
+    let invalid = || format!("invalid range \"{}\", expected e.g. 1920-2002", text);
+    let (min, max) = text.split_once('-').ok_or_else(invalid)?;
+    let min = min.parse().map_err(|_| invalid())?;
+    let max = max.parse().map_err(|_| invalid())?;
+    Ok((min, max))
+}
+
+// "ignore", "range 1920-2002", "units 150-193cm 59-76in", "pattern #[0-9a-f]{6}" or
+// "one-of amb blu brn"
+impl FromStr for Validator {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (kind, args) = text.trim().split_once(' ').unwrap_or((text.trim(), ""));
+        let args = args.trim();
+        match kind {
+            "ignore" => Ok(Self::Ignored),
+            "range" => {
+                let (min, max) = parse_range(args)?;
+                Ok(Self::Range(min, max))
+            }
+            "units" => args
+                .split_whitespace()
+                .map(|unit_range| {
+                    let unit_start = unit_range
+                        .rfind(|c: char| c.is_ascii_digit())
+                        .map_or(0, |idx| idx + 1);
+                    let (range, unit) = unit_range.split_at(unit_start);
+                    let (min, max) = parse_range(range)?;
+                    Ok((unit.to_string(), min, max))
+                })
+                .collect::<Result<_, String>>()
+                .map(Self::Units),
+            "pattern" => Ok(Self::Pattern(args.parse()?)),
+            "one-of" => Ok(Self::OneOf(
+                args.split_whitespace().map(str::to_string).collect(),
+            )),
+            _ => Err(format!(
+                "invalid validator \"{}\", expected ignore, range, units, pattern or one-of",
+                text
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    key: String,
+    required: bool,
+    validator: Validator,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    fields: Vec<Field>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self { fields: Vec::new() }
+    }
+
+    pub fn required(self, key: &str, validator: Validator) -> Self {
+        self.field(key, true, validator)
+    }
+
+    pub fn optional(self, key: &str, validator: Validator) -> Self {
+        self.field(key, false, validator)
+    }
+
+    fn field(mut self, key: &str, required: bool, validator: Validator) -> Self {
+        self.fields.retain(|field| field.key != key);
+        self.fields.push(Field {
+            key: key.to_string(),
+            required,
+            validator,
+        });
+        self
+    }
+
+    // the rules from the puzzle
+    fn passport() -> Self {
+        let pattern = |source: &str| Validator::Pattern(source.parse().unwrap());
+        let eye_colors = ["amb", "blu", "brn", "gry", "grn", "hzl", "oth"];
+        Self::new()
+            .required("byr", Validator::Range(1920, 2002))
+            .required("iyr", Validator::Range(2010, 2020))
+            .required("eyr", Validator::Range(2020, 2030))
+            .required(
+                "hgt",
+                Validator::Units(vec![
+                    ("cm".to_string(), 150, 193),
+                    ("in".to_string(), 59, 76),
+                ]),
+            )
+            .required("hcl", pattern("#[0-9a-fA-F]{6}"))
+            .required(
+                "ecl",
+                Validator::OneOf(eye_colors.iter().map(|color| color.to_string()).collect()),
+            )
+            .required("pid", pattern("\\d{9}"))
+            .optional("cid", Validator::Ignored)
+    }
+}
+
+// one field per line, as "<key> required|optional <validator>", skipping blank lines and lines
+// starting with "//"
+impl FromStr for Schema {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut schema = Self::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let error = |reason: String| format!("line {}: {}", idx + 1, reason);
+            let mut parts = line.splitn(3, char::is_whitespace);
+            let (key, presence, validator) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(key), Some(presence), Some(validator)) => (key, presence, validator),
+                _ => {
+                    return Err(error(format!(
+                        "expected \"<key> required|optional <validator>\", found \"{}\"",
+                        line
+                    )))
+                }
+            };
+            let validator = validator.parse().map_err(error)?;
+            schema = match presence {
+                "required" => schema.required(key, validator),
+                "optional" => schema.optional(key, validator),
+                _ => {
+                    return Err(error(format!(
+                        "expected required or optional, found \"{}\"",
+                        presence
+                    )))
+                }
+            };
+        }
+        Ok(schema)
     }
 }
 
 pub struct Passport<'a> {
-    fields: HashMap<&'a str, &'a str>,
+    // 1-based line numbers in the batch file
+    lines: RangeInclusive<usize>,
+    // in the order given, including unknown and duplicated keys
+    fields: Vec<(&'a str, &'a str)>,
 }
 
 impl<'a> Passport<'a> {
-    fn new() -> Self {
-        Self {
-            fields: HashMap::new(),
+    fn values(&self, key: &str) -> Vec<&'a str> {
+        self.fields
+            .iter()
+            .filter(|pair| pair.0 == key)
+            .map(|pair| pair.1)
+            .collect()
+    }
+
+    fn validate_keys(&self, schema: &Schema) -> bool {
+        schema
+            .fields
+            .iter()
+            .all(|field| !field.required || self.fields.iter().any(|pair| pair.0 == field.key))
+    }
+
+    // a finding for each field in the schema apart from absent optional ones, followed by one
+    // for each unknown key
+    fn diagnose<'s>(&'s self, schema: &'s Schema) -> Vec<(&'s str, Finding)> {
+        let mut findings: Vec<_> = schema
+            .fields
+            .iter()
+            .filter_map(|field| {
+                let values = self.values(&field.key);
+                let finding = match values.as_slice() {
+                    [] if field.required => Finding::Invalid(Problem::Missing),
+                    [] => return None,
+                    [_, _, ..] => Finding::Invalid(Problem::Duplicated(
+                        values.iter().map(|value| value.to_string()).collect(),
+                    )),
+                    [_] if field.validator == Validator::Ignored => Finding::Ignored,
+                    [value] => match field.validator.check(value) {
+                        Ok(()) => Finding::Valid,
+                        Err(problem) => Finding::Invalid(problem),
+                    },
+                };
+                Some((field.key.as_str(), finding))
+            })
+            .collect();
+        for &(key, _) in self.fields.iter() {
+            let known = schema.fields.iter().any(|field| field.key == key);
+            if !known && !findings.iter().any(|pair| pair.0 == key) {
+                findings.push((key, Finding::Unknown));
+            }
         }
+        findings
     }
 
-    fn validate_keys(&self) -> Option<KeyedPassport<'a>> {
-        Some(KeyedPassport {
-            birth_year: self.fields.get("byr")?,
-            issue_year: self.fields.get("iyr")?,
-            expiration_year: self.fields.get("eyr")?,
-            height: self.fields.get("hgt")?,
-            hair_color: self.fields.get("hcl")?,
-            eye_color: self.fields.get("ecl")?,
-            passport_id: self.fields.get("pid")?,
-        })
+    // unknown keys are allowed, as are absent optional ones
+    fn valid(&self, schema: &Schema) -> bool {
+        self.diagnose(schema)
+            .iter()
+            .all(|pair| !matches!(pair.1, Finding::Invalid(_)))
+    }
+}
+
+fn print_report(passports: &[Passport], schema: &Schema) {
+    let mut problem_counts: HashMap<(&str, &str), usize> = HashMap::new();
+    for (idx, passport) in passports.iter().enumerate() {
+        let verdict = if passport.valid(schema) {
+            "valid"
+        } else {
+            "invalid"
+        };
+        println!(
+            "  passport {} (lines {}-{}): {}",
+            idx + 1,
+            passport.lines.start(),
+            passport.lines.end(),
+            verdict
+        );
+        for (key, finding) in passport.diagnose(schema) {
+            match finding {
+                Finding::Valid => {}
+                Finding::Ignored => println!("    {}: ignored", key),
+                Finding::Unknown => println!("    {}: unknown key", key),
+                Finding::Invalid(problem) => {
+                    println!("    {}: {}", key, problem);
+                    *problem_counts.entry((key, problem.summary())).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+    let mut problem_counts: Vec<_> = problem_counts.into_iter().collect();
+    problem_counts.sort_unstable_by_key(|&(problem, count)| (Reverse(count), problem));
+    println!();
+    println!("  problems:");
+    for ((key, summary), count) in problem_counts {
+        println!("    {}: {} ({})", key, summary, count);
+    }
+}
+
+// whitespace separated "key:value" pairs, where the value may be empty, spread over any number of
+// lines
+fn passport<'a>() -> impl Parser<'a, Passport<'a>> {
+    let space = || take_while1(char::is_whitespace, "whitespace");
+    let field = pair(
+        terminated(
+            take_while1(|c| c != ':' && !c.is_whitespace(), "a key"),
+            literal(":"),
+        ),
+        map(
+            optional(take_while1(|c| !c.is_whitespace(), "a value")),
+            |value| value.unwrap_or(""),
+        ),
+    );
+    move |input: Input<'a>| {
+        let mut fields = Vec::new();
+        let (_, mut rest) = optional(space()).parse(input)?;
+        while !rest.is_empty() {
+            let (next, after) = field.parse(rest)?;
+            fields.push(next);
+            (_, rest) = optional(space()).parse(after)?;
+        }
+        let passport = Passport {
+            lines: input.line()..=rest.line(),
+            fields,
+        };
+        Ok((passport, rest))
     }
 }
 
 impl<'a> Day<'a> for Day04 {
     type Input1 = Vec<Passport<'a>>;
-    type Input2 = Vec<KeyedPassport<'a>>;
+    type Input2 = Vec<Passport<'a>>;
     type Output1 = usize;
     type Output2 = usize;
 
     const INDEX: usize = 4;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        let mut passports = Vec::new();
-        let lines: Vec<_> = raw_input.lines().collect();
-        for passport_lines in lines.split(|line| line.is_empty()) {
-            let mut passport = Passport::new();
-            for line in passport_lines {
-                for pair in line.split(' ') {
-                    let (key, colon_value) = pair.split_at(3);
-                    passport.fields.insert(key, &colon_value[1..]);
-                }
-            }
-            passports.push(passport);
-        }
-        passports
+        parse::parse_all(blocks(passport()), raw_input)
+            .unwrap_or_else(|err| panic!("failed to parse passports: {}", err))
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
+        let schema = Schema::passport();
         let keyed_passports: Vec<_> = input
-            .iter()
-            .filter_map(|passport| passport.validate_keys())
+            .into_iter()
+            .filter(|passport| passport.validate_keys(&schema))
             .collect();
         let count = keyed_passports.len();
         (keyed_passports, count)
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output1 {
-        input.iter().filter(|passport| passport.valid()).count()
+        let schema = Schema::passport();
+        input
+            .iter()
+            .filter(|passport| passport.valid(&schema))
+            .count()
+    }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(args, &["--schema", "--report"])?;
+        let schema = match flags.get::<String>("--schema")? {
+            Some(path) => fs::read_to_string(&path)
+                .map_err(|err| format!("failed to read \"{}\": {}", path, err))?
+                .parse()
+                .map_err(|err| format!("invalid schema \"{}\": {}", path, err))?,
+            None => Schema::passport(),
+        };
+        let passports = Self::parse(raw_input);
+        let keyed = passports
+            .iter()
+            .filter(|passport| passport.validate_keys(&schema))
+            .count();
+        let valid = passports
+            .iter()
+            .filter(|passport| passport.valid(&schema))
+            .count();
+        println!();
+        println!("day{:02}:", Self::INDEX);
+        if flags.has("--report") {
+            print_report(&passports, &schema);
+            println!();
+        }
+        println!("  required fields present: {}", keyed);
+        println!("  valid: {}", valid);
+        Ok(())
     }
 }
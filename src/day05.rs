@@ -1,52 +1,350 @@
-use crate::day::Day;
-
-type Seat = usize;
-
-fn parse_seat(desc: &str) -> Seat {
-    desc.chars()
-        .map(|c| match c {
-            'F' => 0,
-            'B' => 1,
-            'L' => 0,
-            'R' => 1,
-            _ => unreachable!(),
+use crate::{
+    day::Day,
+    util::{Flags, Grid2D},
+};
+use std::{fmt, ops::Range};
+
+// rows are picked with F/B and columns with L/R, one binary digit per letter, high bits first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Geometry {
+    row_bits: u32,
+    column_bits: u32,
+}
+
+impl Geometry {
+    pub const PUZZLE: Self = Self {
+        row_bits: 7,
+        column_bits: 3,
+    };
+
+    pub fn new(row_bits: u32, column_bits: u32) -> Result<Self, String> {
+        if row_bits
+            .checked_add(column_bits)
+            .is_none_or(|bits| bits >= usize::BITS)
+        {
+            return Err(format!(
+                "{} row bits and {} column bits won't fit in a seat ID",
+                row_bits, column_bits
+            ));
+        }
+        Ok(Self {
+            row_bits,
+            column_bits,
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        1 << self.row_bits
+    }
+
+    pub fn columns(&self) -> usize {
+        1 << self.column_bits
+    }
+
+    pub fn seat(&self, row: usize, column: usize) -> Result<BoardingPass, String> {
+        if row >= self.rows() || column >= self.columns() {
+            return Err(format!(
+                "seat ({}, {}) is outside the {} by {} plane",
+                row,
+                column,
+                self.rows(),
+                self.columns()
+            ));
+        }
+        Ok(BoardingPass {
+            geometry: *self,
+            row,
+            column,
         })
-        .rev()
-        .zip(0..)
-        .map(|pair| pair.0 * (1 << pair.1))
-        .sum()
+    }
+
+    pub fn seat_with_id(&self, seat_id: usize) -> Result<BoardingPass, String> {
+        self.seat(seat_id >> self.column_bits, seat_id & (self.columns() - 1))
+    }
+
+    pub fn decode(&self, text: &str) -> Result<BoardingPass, String> {
+        let expected_len = (self.row_bits + self.column_bits) as usize;
+        let letters: Vec<char> = text.chars().collect();
+        if letters.len() != expected_len {
+            return Err(format!(
+                "expected {} letters in {:?}, found {}",
+                expected_len,
+                text,
+                letters.len()
+            ));
+        }
+        let (row_letters, column_letters) = letters.split_at(self.row_bits as usize);
+        let row = decode_bits(row_letters, ['F', 'B'], 0, text)?;
+        let column = decode_bits(column_letters, ['L', 'R'], self.row_bits as usize, text)?;
+        self.seat(row, column)
+    }
+}
+
+fn decode_bits(
+    letters: &[char],
+    digits: [char; 2],
+    offset: usize,
+    text: &str,
+) -> Result<usize, String> {
+    letters
+        .iter()
+        .enumerate()
+        .try_fold(0, |value, (idx, &letter)| {
+            match digits.iter().position(|&digit| digit == letter) {
+                Some(bit) => Ok(value << 1 | bit),
+                None => Err(format!(
+                    "expected {} or {} at position {} of {:?}, found {:?}",
+                    digits[0],
+                    digits[1],
+                    offset + idx + 1,
+                    text,
+                    letter
+                )),
+            }
+        })
+}
+
+fn encode_bits(value: usize, bits: u32, digits: [char; 2]) -> impl Iterator<Item = char> {
+    (0..bits).rev().map(move |bit| digits[(value >> bit) & 1])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardingPass {
+    geometry: Geometry,
+    row: usize,
+    column: usize,
+}
+
+impl BoardingPass {
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn seat_id(&self) -> usize {
+        self.row << self.geometry.column_bits | self.column
+    }
+}
+
+// encodes the pass, so it displays as the letters it was decoded from
+impl fmt::Display for BoardingPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let letters: String = encode_bits(self.row, self.geometry.row_bits, ['F', 'B'])
+            .chain(encode_bits(
+                self.column,
+                self.geometry.column_bits,
+                ['L', 'R'],
+            ))
+            .collect();
+        write!(f, "{}", letters)
+    }
+}
+
+fn parse_passes(raw_input: &str, geometry: Geometry) -> Result<Vec<BoardingPass>, String> {
+    raw_input
+        .lines()
+        .enumerate()
+        .filter(|pair| !pair.1.trim().is_empty())
+        .map(|(idx, line)| {
+            geometry
+                .decode(line.trim())
+                .map_err(|err| format!("line {}: {}", idx + 1, err))
+        })
+        .collect()
+}
+
+// the planes the seat map is drawn for, as bigger ones would flood the terminal
+const MAP_SEAT_LIMIT: usize = 1 << 16;
+
+// the seat IDs before the first and after the last occupied seat, which don't exist on the plane,
+// and the runs of empty seats in between
+// seat_ids must be sorted and deduplicated
+fn missing_seats(
+    seat_ids: &[usize],
+    geometry: Geometry,
+) -> (Range<usize>, Vec<Range<usize>>, Range<usize>) {
+    let seats = geometry.rows() * geometry.columns();
+    let (first, last) = match (seat_ids.first(), seat_ids.last()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return (0..seats, Vec::new(), seats..seats),
+    };
+    let gaps = seat_ids
+        .windows(2)
+        .filter(|pair| pair[1] > pair[0] + 1)
+        .map(|pair| pair[0] + 1..pair[1])
+        .collect();
+    (0..first, gaps, last + 1..seats)
+}
+
+// names the first and last seat, as a run can start or end part way along a row
+fn describe_range(seat_ids: &Range<usize>, geometry: Geometry) -> String {
+    if seat_ids.is_empty() {
+        return "none".to_string();
+    }
+    let first = geometry.seat_with_id(seat_ids.start).unwrap();
+    let last = geometry.seat_with_id(seat_ids.end - 1).unwrap();
+    format!(
+        "{} seats, IDs {}-{} (row {} column {} to row {} column {})",
+        seat_ids.len(),
+        seat_ids.start,
+        seat_ids.end - 1,
+        first.row(),
+        first.column(),
+        last.row(),
+        last.column()
+    )
+}
+
+// one character per seat, # for a single pass, . for none and the number of passes for duplicates
+fn print_seat_map(passes: &[BoardingPass], geometry: Geometry) {
+    let seats = geometry.rows() * geometry.columns();
+    if seats > MAP_SEAT_LIMIT {
+        println!(
+            "  the plane has {} seats, too many to draw a map of (at most {})",
+            seats, MAP_SEAT_LIMIT
+        );
+        return;
+    }
+    let mut occupancy = Grid2D::filled(geometry.columns(), geometry.rows(), 0usize);
+    for pass in passes {
+        occupancy[(pass.column(), pass.row())] += 1;
+    }
+    let map = occupancy.render(|&count| match count {
+        0 => '.',
+        1 => '#',
+        _ => std::char::from_digit(count.min(9) as u32, 10).unwrap(),
+    });
+    for (row, line) in map.lines().enumerate() {
+        println!("  {:>4} {}", row, line);
+    }
+}
+
+fn print_analysis(passes: &[BoardingPass], geometry: Geometry) {
+    print_seat_map(passes, geometry);
+    let mut seat_ids: Vec<_> = passes.iter().map(BoardingPass::seat_id).collect();
+    seat_ids.sort_unstable();
+    let duplicates: Vec<_> = seat_ids
+        .chunk_by(|a, b| a == b)
+        .filter(|run| run.len() > 1)
+        .map(|run| (geometry.seat_with_id(run[0]).unwrap(), run.len()))
+        .collect();
+    seat_ids.dedup();
+    let (front, gaps, back) = missing_seats(&seat_ids, geometry);
+    println!();
+    println!(
+        "  missing at the front: {}",
+        describe_range(&front, geometry)
+    );
+    println!("  missing at the back: {}", describe_range(&back, geometry));
+    println!("  gaps: {}", gaps.iter().map(Range::len).sum::<usize>());
+    for gap in gaps {
+        if gap.len() > 1 {
+            println!("    {}", describe_range(&gap, geometry));
+            continue;
+        }
+        let pass = geometry.seat_with_id(gap.start).unwrap();
+        println!(
+            "    {} (row {}, column {}, {})",
+            gap.start,
+            pass.row(),
+            pass.column(),
+            pass
+        );
+    }
+    println!("  duplicates: {}", duplicates.len());
+    for (pass, count) in duplicates {
+        println!("    {} ({}) on {} passes", pass.seat_id(), pass, count);
+    }
 }
 
 pub struct Day05 {}
 
 impl<'a> Day<'a> for Day05 {
-    type Input1 = Vec<Seat>;
-    type Input2 = Vec<Seat>;
+    type Input1 = Vec<BoardingPass>;
+    type Input2 = Vec<BoardingPass>;
     type Output1 = usize;
     type Output2 = usize;
 
     const INDEX: usize = 5;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        raw_input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| parse_seat(&line[0..10]))
-            .collect()
+        parse_passes(raw_input, Geometry::PUZZLE)
+            .unwrap_or_else(|err| panic!("failed to parse boarding passes: {}", err))
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
-        let max_id = *input.iter().max().unwrap();
+        let max_id = input.iter().map(BoardingPass::seat_id).max().unwrap();
         (input, max_id)
     }
 
-    fn solve_part2(mut input: Self::Input2) -> Self::Output2 {
-        input.sort();
-        let (left, _right) = input
+    fn solve_part2(input: Self::Input2) -> Self::Output2 {
+        let mut seat_ids: Vec<_> = input.iter().map(BoardingPass::seat_id).collect();
+        seat_ids.sort();
+        let (left, _right) = seat_ids
             .iter()
-            .zip(input.iter().skip(1))
+            .zip(seat_ids.iter().skip(1))
             .find(|pair| pair.1 - pair.0 == 2)
             .unwrap();
         left + 1
     }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(
+            args,
+            &[
+                "--row-bits",
+                "--column-bits",
+                "--decode",
+                "--encode",
+                "--analyse",
+            ],
+        )?;
+        let geometry = Geometry::new(
+            flags
+                .get("--row-bits")?
+                .unwrap_or(Geometry::PUZZLE.row_bits),
+            flags
+                .get("--column-bits")?
+                .unwrap_or(Geometry::PUZZLE.column_bits),
+        )?;
+        let pass = match (flags.get::<String>("--decode")?, flags.get("--encode")?) {
+            (Some(text), None) => Some(geometry.decode(&text)?),
+            (None, Some(seat_id)) => Some(geometry.seat_with_id(seat_id)?),
+            (None, None) => None,
+            (Some(_), Some(_)) => {
+                return Err("expected only one of --decode and --encode".to_string())
+            }
+        };
+        let passes = match pass {
+            Some(_) => Vec::new(),
+            None => parse_passes(raw_input, geometry)?,
+        };
+        println!();
+        println!(
+            "day{:02} ({} rows of {} seats):",
+            Self::INDEX,
+            geometry.rows(),
+            geometry.columns()
+        );
+        if let Some(pass) = pass {
+            println!("  pass: {}", pass);
+            println!("  row: {}", pass.row());
+            println!("  column: {}", pass.column());
+            println!("  seat ID: {}", pass.seat_id());
+            return Ok(());
+        }
+        if flags.has("--analyse") {
+            print_analysis(&passes, geometry);
+            return Ok(());
+        }
+        let highest = passes.iter().max_by_key(|pass| pass.seat_id());
+        match highest {
+            Some(pass) => println!("  highest seat ID: {} ({})", pass.seat_id(), pass),
+            None => println!("  no boarding passes"),
+        }
+        Ok(())
+    }
 }
@@ -1,31 +1,351 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{
+        parse::{self, blocks, line_ending, take_while1, Input, ParseError, ParseResult, Parser},
+        Flags,
+    },
+};
+use std::{fmt, str::FromStr};
 
-#[derive(Clone, Copy)]
-pub struct AlphabetSet {
-    flags: u32,
+// the questions that can be answered, each getting the next bit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alphabet {
+    letters: Vec<char>,
+}
+
+impl Alphabet {
+    pub fn new(letters: impl IntoIterator<Item = char>) -> Result<Self, String> {
+        let mut alphabet = Self {
+            letters: Vec::new(),
+        };
+        for letter in letters {
+            if alphabet.index(letter).is_some() {
+                return Err(format!("{:?} appears twice in the alphabet", letter));
+            }
+            if letter.is_whitespace() {
+                return Err(format!(
+                    "the alphabet can't contain whitespace, found {:?}",
+                    letter
+                ));
+            }
+            alphabet.letters.push(letter);
+        }
+        Ok(alphabet)
+    }
+
+    pub fn lowercase() -> Self {
+        Self::new('a'..='z').unwrap()
+    }
+
+    // every letter in the text, in the order they first appear
+    pub fn inferred(text: &str) -> Self {
+        let mut alphabet = Self {
+            letters: Vec::new(),
+        };
+        for letter in text.chars().filter(|c| !c.is_whitespace()) {
+            if alphabet.index(letter).is_none() {
+                alphabet.letters.push(letter);
+            }
+        }
+        alphabet
+    }
+
+    // every letter, for questions nobody answered
+    fn full_set(&self) -> AlphabetSet {
+        let mut set = AlphabetSet::new();
+        (0..self.letters.len()).for_each(|idx| set.insert(idx));
+        set
+    }
+
+    fn index(&self, letter: char) -> Option<usize> {
+        self.letters.iter().position(|&other| other == letter)
+    }
+
+    // one person's answers, with an error pointing at the first letter outside the alphabet
+    fn answers<'a>(&self, input: Input<'a>) -> ParseResult<'a, AlphabetSet> {
+        let (letters, rest) = take_while1(|c| !c.is_whitespace(), "answers").parse(input)?;
+        let mut set = AlphabetSet::new();
+        for (offset, letter) in letters.char_indices() {
+            match self.index(letter) {
+                Some(idx) => set.insert(idx),
+                None => {
+                    let expected = format!("one of {:?}", self.letters.iter().collect::<String>());
+                    return Err(input.advance(offset).error(expected));
+                }
+            }
+        }
+        Ok((set, rest))
+    }
+
+    pub fn parse_groups<'a>(
+        &self,
+        raw_input: &'a str,
+    ) -> Result<Vec<Vec<AlphabetSet>>, ParseError> {
+        // a line per person, where unlike separated a bad line is an error rather than the end
+        let group = |input: Input<'a>| {
+            let (first, mut rest) = self.answers(input)?;
+            let mut people = vec![first];
+            while let Ok((_, next_line)) = line_ending().parse(rest) {
+                let (person, after) = self.answers(next_line)?;
+                people.push(person);
+                rest = after;
+            }
+            Ok((people, rest))
+        };
+        parse::parse_all(blocks(group), raw_input)
+    }
+}
+
+// starts as a u32 and switches to a u128 and then a bitset as bigger letters are added, with both
+// sides widened to match before combining two sets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlphabetSet {
+    Small(u32),
+    Medium(u128),
+    Large(Vec<u64>),
 }
 
 impl AlphabetSet {
-    fn from(string: &str) -> Self {
-        Self {
-            flags: string.bytes().fold(0, |flags, c| flags | (1 << (c - b'a'))),
+    fn new() -> Self {
+        Self::Small(0)
+    }
+
+    fn capacity(&self) -> usize {
+        match self {
+            Self::Small(_) => 32,
+            Self::Medium(_) => 128,
+            Self::Large(words) => 64 * words.len(),
         }
     }
 
-    fn union(self, other: &Self) -> Self {
-        Self {
-            flags: self.flags | other.flags,
+    fn grow_to(&mut self, bits: usize) {
+        if bits <= self.capacity() {
+            return;
+        }
+        let words = self.words();
+        *self = if bits <= 128 {
+            Self::Medium(words[0] as u128 | (words.get(1).copied().unwrap_or(0) as u128) << 64)
+        } else {
+            let mut words = words;
+            words.resize(bits.div_ceil(64), 0);
+            Self::Large(words)
+        };
+    }
+
+    fn words(&self) -> Vec<u64> {
+        match *self {
+            Self::Small(flags) => vec![flags as u64],
+            Self::Medium(flags) => vec![flags as u64, (flags >> 64) as u64],
+            Self::Large(ref words) => words.clone(),
+        }
+    }
+
+    fn insert(&mut self, idx: usize) {
+        self.grow_to(idx + 1);
+        match self {
+            Self::Small(flags) => *flags |= 1 << idx,
+            Self::Medium(flags) => *flags |= 1 << idx,
+            Self::Large(words) => words[idx / 64] |= 1 << (idx % 64),
         }
     }
 
+    // applies op to each pair of 64 bit words, only copying a side when it needs widening
+    fn combine<F>(&self, other: &Self, op: F) -> Self
+    where
+        F: Fn(u64, u64) -> u64,
+    {
+        match (self, other) {
+            (Self::Small(a), Self::Small(b)) => Self::Small(op(*a as u64, *b as u64) as u32),
+            (Self::Medium(a), Self::Medium(b)) => {
+                let low = op(*a as u64, *b as u64) as u128;
+                let high = op((a >> 64) as u64, (b >> 64) as u64) as u128;
+                Self::Medium(low | high << 64)
+            }
+            (Self::Large(a), Self::Large(b)) if a.len() == b.len() => {
+                Self::Large(a.iter().zip(b.iter()).map(|(&a, &b)| op(a, b)).collect())
+            }
+            _ => {
+                let bits = self.capacity().max(other.capacity());
+                let (mut left, mut right) = (self.clone(), other.clone());
+                left.grow_to(bits);
+                right.grow_to(bits);
+                left.combine(&right, op)
+            }
+        }
+    }
+
+    fn union(self, other: &Self) -> Self {
+        self.combine(other, |a, b| a | b)
+    }
+
     fn intersection(self, other: &Self) -> Self {
-        Self {
-            flags: self.flags & other.flags,
+        self.combine(other, |a, b| a & b)
+    }
+
+    fn difference(self, other: &Self) -> Self {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    fn symmetric_difference(self, other: &Self) -> Self {
+        self.combine(other, |a, b| a ^ b)
+    }
+
+    fn contains(&self, idx: usize) -> bool {
+        match self {
+            Self::Small(flags) => idx < 32 && flags >> idx & 1 == 1,
+            Self::Medium(flags) => idx < 128 && flags >> idx & 1 == 1,
+            Self::Large(words) => words
+                .get(idx / 64)
+                .is_some_and(|word| word >> (idx % 64) & 1 == 1),
         }
     }
 
     fn count(&self) -> u32 {
-        self.flags.count_ones()
+        match self {
+            Self::Small(flags) => flags.count_ones(),
+            Self::Medium(flags) => flags.count_ones(),
+            Self::Large(words) => words.iter().map(|word| word.count_ones()).sum(),
+        }
+    }
+}
+
+fn union_count(group: &[AlphabetSet]) -> u32 {
+    group[1..]
+        .iter()
+        .fold(group[0].clone(), |counter, person| counter.union(person))
+        .count()
+}
+
+fn intersection_count(group: &[AlphabetSet]) -> u32 {
+    group[1..]
+        .iter()
+        .fold(group[0].clone(), |counter, person| {
+            counter.intersection(person)
+        })
+        .count()
+}
+
+// levels[j] holds the questions answered by at least j people in the group, for j up to max
+fn answer_levels(group: &[AlphabetSet], alphabet: &Alphabet, max: usize) -> Vec<AlphabetSet> {
+    let mut levels = vec![AlphabetSet::new(); max + 1];
+    levels[0] = alphabet.full_set();
+    for person in group {
+        for j in (1..=max).rev() {
+            let newly_reached = levels[j - 1].clone().intersection(person);
+            levels[j] = levels[j].clone().union(&newly_reached);
+        }
+    }
+    levels
+}
+
+// which questions to count in each group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    Anyone,
+    Everyone,
+    AtLeast(usize),
+    Exactly(usize),
+    // answered by an odd number of people
+    SymmetricDifference,
+}
+
+impl Condition {
+    fn count(self, group: &[AlphabetSet], alphabet: &Alphabet) -> u32 {
+        match self {
+            Self::Anyone => union_count(group),
+            Self::Everyone => intersection_count(group),
+            // no question is answered by more people than there are in the group
+            Self::AtLeast(k) | Self::Exactly(k) if k > group.len() => 0,
+            Self::AtLeast(k) => answer_levels(group, alphabet, k)[k].count(),
+            Self::Exactly(k) => {
+                let levels = answer_levels(group, alphabet, k + 1);
+                levels[k].clone().difference(&levels[k + 1]).count()
+            }
+            Self::SymmetricDifference => group
+                .iter()
+                .fold(AlphabetSet::new(), |odd, person| {
+                    odd.symmetric_difference(person)
+                })
+                .count(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Query {
+    // the number of questions meeting the condition, summed over the groups
+    Count(Condition),
+    // how many people answered each question, over every group
+    Histogram,
+}
+
+impl FromStr for Query {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (name, count) = match text.split_once(':') {
+            Some((name, count)) => (name, Some(count)),
+            None => (text, None),
+        };
+        let count = || -> Result<usize, String> {
+            count
+                .ok_or_else(|| format!("expected a count after \"{}\", e.g. {}:2", name, name))?
+                .parse()
+                .map_err(|_| format!("invalid count in query \"{}\"", text))
+        };
+        let condition = match name {
+            "anyone" => Condition::Anyone,
+            "everyone" => Condition::Everyone,
+            "at-least" => Condition::AtLeast(count()?),
+            "exactly" => Condition::Exactly(count()?),
+            "symmetric-difference" => Condition::SymmetricDifference,
+            "histogram" => return Ok(Self::Histogram),
+            _ => {
+                return Err(format!(
+                    "invalid query \"{}\", expected anyone, everyone, at-least:K, exactly:K, \
+                     symmetric-difference or histogram",
+                    text
+                ))
+            }
+        };
+        Ok(Self::Count(condition))
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Anyone => write!(f, "anyone"),
+            Self::Everyone => write!(f, "everyone"),
+            Self::AtLeast(k) => write!(f, "at-least:{}", k),
+            Self::Exactly(k) => write!(f, "exactly:{}", k),
+            Self::SymmetricDifference => write!(f, "symmetric-difference"),
+        }
+    }
+}
+
+fn print_count(groups: &[Vec<AlphabetSet>], alphabet: &Alphabet, condition: Condition) {
+    let count: u32 = groups
+        .iter()
+        .map(|group| condition.count(group, alphabet))
+        .sum();
+    println!("  {}: {}", condition, count);
+}
+
+fn print_histogram(groups: &[Vec<AlphabetSet>], alphabet: &Alphabet) {
+    let frequencies: Vec<_> = (0..alphabet.letters.len())
+        .map(|idx| {
+            groups
+                .iter()
+                .flatten()
+                .filter(|person| person.contains(idx))
+                .count()
+        })
+        .collect();
+    let most = frequencies.iter().copied().max().unwrap_or(0).max(1);
+    for (letter, frequency) in alphabet.letters.iter().zip(frequencies) {
+        let bar = "#".repeat((frequency * 50).div_ceil(most));
+        println!("  {} {:>5} {}", letter, frequency, bar);
     }
 }
 
@@ -40,36 +360,47 @@ impl<'a> Day<'a> for Day06 {
     const INDEX: usize = 6;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        let lines: Vec<_> = raw_input.lines().collect();
-        lines
-            .split(|line| line.is_empty())
-            .filter(|group| !group.is_empty())
-            .map(|group| group.iter().map(|s| AlphabetSet::from(s)).collect())
-            .collect()
+        Alphabet::lowercase()
+            .parse_groups(raw_input)
+            .unwrap_or_else(|err| panic!("failed to parse answers: {}", err))
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
-        let sum = input
-            .iter()
-            .map(|group| {
-                group[1..]
-                    .iter()
-                    .fold(group[0], |counter, person| counter.union(person))
-                    .count()
-            })
-            .sum();
+        let sum = input.iter().map(|group| union_count(group)).sum();
         (input, sum)
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
-        input
-            .iter()
-            .map(|group| {
-                group[1..]
-                    .iter()
-                    .fold(group[0], |counter, person| counter.intersection(person))
-                    .count()
-            })
-            .sum()
+        input.iter().map(|group| intersection_count(group)).sum()
+    }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(args, &["--alphabet", "--query"])?;
+        let query: Option<Query> = flags.get("--query")?;
+        // "any" takes the alphabet from the input itself
+        let alphabet = match flags.get::<String>("--alphabet")? {
+            Some(letters) if letters == "any" => Alphabet::inferred(raw_input),
+            Some(letters) => Alphabet::new(letters.chars())?,
+            None => Alphabet::lowercase(),
+        };
+        let groups = alphabet
+            .parse_groups(raw_input)
+            .map_err(|err| format!("failed to parse answers: {}", err))?;
+        println!();
+        println!(
+            "day{:02} ({} letter alphabet):",
+            Self::INDEX,
+            alphabet.letters.len()
+        );
+        match query {
+            Some(Query::Histogram) => print_histogram(&groups, &alphabet),
+            Some(Query::Count(condition)) => print_count(&groups, &alphabet, condition),
+            None => {
+                for condition in [Condition::Anyone, Condition::Everyone] {
+                    print_count(&groups, &alphabet, condition);
+                }
+            }
+        }
+        Ok(())
     }
 }
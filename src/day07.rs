@@ -1,20 +1,75 @@
-use crate::day::Day;
-use std::collections::{HashMap, HashSet};
+use crate::{
+    day::Day,
+    util::{
+        graph::Graph,
+        parse::{
+            self, either, integer, lines, literal, map, pair, preceded, recognize, separated,
+            terminated, word, Parser,
+        },
+        Flags,
+    },
+};
 
-fn take_words(count: usize, string: &str) -> (&str, &str) {
-    string
-        .chars()
-        .enumerate()
-        .filter(|pair| pair.1 == ' ')
-        .nth(count - 1)
-        .map(|pair| (&string[..pair.0], &string[pair.0 + 1..]))
-        .unwrap_or((string, ""))
+fn color<'a>() -> impl Parser<'a, &'a str> {
+    recognize(pair(word(), preceded(literal(" "), word())))
 }
 
-#[derive(Debug)]
-pub struct RuleSet<'a> {
-    child_map: HashMap<&'a str, Vec<(usize, &'a str)>>,
-    parent_map: HashMap<&'a str, HashSet<&'a str>>,
+// edges go from each bag to the bags directly inside it, weighted by how many
+pub type RuleSet<'a> = Graph<&'a str, usize>;
+
+const TARGET: &str = "shiny gold";
+
+fn check_bag(rule_set: &RuleSet, bag: &str) -> Result<(), String> {
+    if rule_set.contains(bag) {
+        Ok(())
+    } else {
+        Err(format!("unknown bag colour \"{}\"", bag))
+    }
+}
+
+fn print_containers(rule_set: &RuleSet, bag: &str, list: bool) -> Result<(), String> {
+    let mut containers: Vec<_> = rule_set
+        .reaching(bag)
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .collect();
+    containers.sort_unstable();
+    println!("  bags that can contain {}: {}", bag, containers.len());
+    if list {
+        for container in containers {
+            println!("    {}", container);
+        }
+    }
+    let contained = rule_set
+        .path_weight_sum(bag)
+        .map_err(|err| format!("failed to count bags inside \"{}\": {}", bag, err))?;
+    println!("  bags inside {}: {}", bag, contained);
+    Ok(())
+}
+
+// one line per step, with how many of each bag end up inside the outer one
+fn print_path(rule_set: &RuleSet, outer: &str, inner: &str) -> Result<(), String> {
+    let steps = rule_set
+        .shortest_path(outer, inner)
+        .map_err(|err| err.to_string())?;
+    let steps = match steps {
+        Some(steps) => steps,
+        None => {
+            println!("  {} can't contain {}", outer, inner);
+            return Ok(());
+        }
+    };
+    println!("  {}", outer);
+    let mut total = 1usize;
+    for (count, bag) in steps {
+        total = total.saturating_mul(count);
+        println!("    contains {} {} ({} in total)", count, bag, total);
+    }
+    let paths = rule_set
+        .count_paths(outer, inner)
+        .map_err(|err| format!("failed to count paths: {}", err))?;
+    println!("  paths from {} to {}: {}", outer, inner, paths);
+    Ok(())
 }
 
 pub struct Day07 {}
@@ -28,63 +83,68 @@ impl<'a> Day<'a> for Day07 {
     const INDEX: usize = 7;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        let mut child_map = HashMap::new();
-        let mut parent_map = HashMap::new();
-        for line in raw_input.lines().filter(|line| !line.is_empty()) {
-            let (parent_color, rest) = take_words(2, line);
-            let (_, children) = take_words(2, rest);
-            if children.starts_with("no") {
-                child_map.insert(parent_color, Vec::new());
-            } else {
-                let mut children = children;
-                while !children.is_empty() {
-                    let (child, remaining_children) = take_words(4, children);
-                    let (child_count, rest) = take_words(1, child);
-                    let child_count = child_count.parse::<usize>().unwrap();
-                    let (child_color, _) = take_words(2, rest);
-                    child_map
-                        .entry(parent_color)
-                        .or_insert_with(Vec::new)
-                        .push((child_count, child_color));
-                    parent_map
-                        .entry(child_color)
-                        .or_insert_with(HashSet::new)
-                        .insert(parent_color);
-                    children = remaining_children;
-                }
-            };
+        let child = pair(
+            terminated(integer(), literal(" ")),
+            terminated(color(), either(literal(" bags"), literal(" bag"))),
+        );
+        let children = either(
+            map(literal("no other bags"), |_| Vec::new()),
+            separated(child, literal(", ")),
+        );
+        let rule = pair(
+            terminated(color(), literal(" bags contain ")),
+            terminated(children, literal(".")),
+        );
+        let rules = parse::parse_all(lines(rule), raw_input)
+            .unwrap_or_else(|err| panic!("failed to parse rules: {}", err));
+        let mut rule_set = Graph::new();
+        for (parent_color, children) in rules {
+            rule_set.add_node(parent_color);
+            for (child_count, child_color) in children {
+                rule_set.add_edge(parent_color, child_color, child_count);
+            }
         }
-        RuleSet {
-            child_map,
-            parent_map,
+        if let Some(cycle) = rule_set.find_cycle() {
+            panic!("bags can't contain themselves: {:?}", cycle);
         }
+        rule_set
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
-        let mut ancestor_set = HashSet::new();
-        let mut stack = vec!["shiny gold"];
-        while let Some(color) = stack.pop() {
-            ancestor_set.insert(color);
-            if let Some(parents) = input.parent_map.get(color) {
-                for parent_color in parents.iter() {
-                    stack.push(parent_color);
-                }
-            }
-        }
-        (input, ancestor_set.len() - 1)
+        let ancestors = input
+            .reaching(TARGET)
+            .unwrap_or_else(|err| panic!("failed to find containing bags: {}", err));
+        (input, ancestors.len())
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output1 {
-        let mut total_count = 0;
-        let mut stack = vec![(1, "shiny gold")];
-        while let Some((count, color)) = stack.pop() {
-            total_count += count;
-            if let Some(children) = input.child_map.get(color) {
-                for (child_count, child_color) in children.iter() {
-                    stack.push((child_count * count, child_color));
-                }
+        input
+            .path_weight_sum(TARGET)
+            .unwrap_or_else(|err| panic!("failed to count contained bags: {}", err))
+    }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(args, &["--bag", "--list", "--path"])?;
+        let rule_set = Self::parse(raw_input);
+        if let Some(path) = flags.get::<String>("--path")? {
+            if flags.has("--bag") || flags.has("--list") {
+                return Err("--path can't be combined with --bag or --list".to_string());
             }
+            let (outer, inner) = path
+                .split_once(',')
+                .ok_or_else(|| format!("invalid path \"{}\", expected OUTER,INNER", path))?;
+            let (outer, inner) = (outer.trim(), inner.trim());
+            check_bag(&rule_set, outer)?;
+            check_bag(&rule_set, inner)?;
+            println!();
+            println!("day{:02} ({} to {}):", Self::INDEX, outer, inner);
+            return print_path(&rule_set, outer, inner);
         }
-        total_count - 1
+        let bag = flags.get::<String>("--bag")?;
+        let bag = bag.as_deref().unwrap_or(TARGET);
+        check_bag(&rule_set, bag)?;
+        println!();
+        println!("day{:02} ({}):", Self::INDEX, bag);
+        print_containers(&rule_set, bag, flags.has("--list"))
     }
 }
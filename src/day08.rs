@@ -1,4 +1,7 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::parse::{self, integer, lines, literal, one_of, pair, terminated},
+};
 use std::collections::HashSet;
 
 struct Failure {
@@ -42,11 +45,12 @@ impl<'a> Day<'a> for Day08 {
     const INDEX: usize = 8;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        raw_input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| (&line[0..3], line[4..].parse::<i32>().unwrap()))
-            .collect()
+        let instruction = pair(
+            terminated(one_of(&["nop", "acc", "jmp"]), literal(" ")),
+            integer(),
+        );
+        parse::parse_all(lines(instruction), raw_input)
+            .unwrap_or_else(|err| panic!("failed to parse program: {}", err))
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
@@ -1,4 +1,4 @@
-use crate::day::Day;
+use crate::{day::Day, util::graph::Graph};
 use std::iter;
 
 pub struct Day10 {}
@@ -47,11 +47,24 @@ impl<'a> Day<'a> for Day10 {
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
-        // I only get up to 7 in this sequence with my input
-        let tribonacci = &[1, 1, 2, 4, 7, 11, 24];
-        input
-            .split(|&n| n == 3)
-            .map(|xs| tribonacci[xs.len()])
-            .product()
+        let jolts: Vec<usize> = iter::once(0)
+            .chain(input.iter().scan(0, |jolt, difference| {
+                *jolt += difference;
+                Some(*jolt)
+            }))
+            .collect();
+        let mut adaptor_chain = Graph::new();
+        for (idx, &jolt) in jolts.iter().enumerate() {
+            adaptor_chain.add_node(jolt);
+            for &next in jolts[idx + 1..]
+                .iter()
+                .take_while(|&&next| next - jolt <= 3)
+            {
+                adaptor_chain.add_edge(jolt, next, ());
+            }
+        }
+        adaptor_chain
+            .count_paths(0, *jolts.last().unwrap())
+            .unwrap_or_else(|err| panic!("failed to count arrangements: {}", err))
     }
 }
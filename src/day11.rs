@@ -1,67 +1,48 @@
-use crate::day::Day;
-
-const DIRECTIONS: &[(i32, i32)] = &[
-    (-1, -1),
-    (-1, 0),
-    (-1, 1),
-    (0, -1),
-    (0, 1),
-    (1, -1),
-    (1, 0),
-    (1, 1),
-];
-
-fn in_range(i: i32, j: i32, ilen: usize, jlen: usize) -> bool {
-    i >= 0 && i < ilen as i32 && j >= 0 && j < jlen as i32
-}
+use crate::{day::Day, util::Grid2D};
 
 fn increment_seats(
-    seats: &mut Vec<Vec<char>>,
-    neighbour_counts: &mut Vec<Vec<usize>>,
+    seats: &mut Grid2D<char>,
+    neighbour_counts: &mut Grid2D<usize>,
     crowded_threshold: usize,
     extended_sight: bool,
 ) -> bool {
-    for (i, neighbour_row) in neighbour_counts.iter_mut().enumerate() {
-        for (j, neighbour_count) in neighbour_row.iter_mut().enumerate() {
-            for (delta_i, delta_j) in DIRECTIONS.iter() {
-                let mut check_i = i as i32 + delta_i;
-                let mut check_j = j as i32 + delta_j;
-                while in_range(check_i, check_j, seats.len(), seats[0].len()) {
-                    match seats[check_i as usize][check_j as usize] {
+    for ((x, y), neighbour_count) in neighbour_counts.iter_mut() {
+        if extended_sight {
+            for ray in seats.rays(x, y) {
+                for pos in ray {
+                    match seats[pos] {
                         '#' => {
                             *neighbour_count += 1;
                             break;
                         }
-                        '.' if extended_sight => {
-                            check_i += delta_i;
-                            check_j += delta_j;
-                            continue;
-                        }
-                        '.' if !extended_sight => break,
-                        'L' => break,
-                        c => panic!("unrecognized character \"{}\"", c),
+                        '.' => continue,
+                        _ => break,
                     }
                 }
             }
+        } else {
+            *neighbour_count = seats
+                .neighbours(x, y)
+                .filter(|&pos| seats[pos] == '#')
+                .count();
         }
     }
 
     let mut changed = false;
-    for (seat_row, neighbour_row) in seats.iter_mut().zip(neighbour_counts.iter_mut()) {
-        for (seat, neighbour_count) in seat_row.iter_mut().zip(neighbour_row.iter_mut()) {
-            match seat {
-                '#' if *neighbour_count >= crowded_threshold => {
-                    *seat = 'L';
-                    changed = true;
-                }
-                'L' if *neighbour_count == 0 => {
-                    *seat = '#';
-                    changed = true;
-                }
-                _ => (),
+    for ((x, y), seat) in seats.iter_mut() {
+        let neighbour_count = &mut neighbour_counts[(x, y)];
+        match seat {
+            '#' if *neighbour_count >= crowded_threshold => {
+                *seat = 'L';
+                changed = true;
+            }
+            'L' if *neighbour_count == 0 => {
+                *seat = '#';
+                changed = true;
             }
-            *neighbour_count = 0;
+            _ => (),
         }
+        *neighbour_count = 0;
     }
     changed
 }
@@ -69,45 +50,33 @@ fn increment_seats(
 pub struct Day11 {}
 
 impl<'a> Day<'a> for Day11 {
-    type Input1 = Vec<Vec<char>>;
-    type Input2 = Vec<Vec<char>>;
+    type Input1 = Grid2D<char>;
+    type Input2 = Grid2D<char>;
     type Output1 = usize;
     type Output2 = usize;
 
     const INDEX: usize = 11;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        raw_input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| line.chars().collect())
-            .collect()
+        Grid2D::parse(raw_input, |c| match c {
+            '.' | 'L' | '#' => Some(c),
+            _ => None,
+        })
+        .expect("failed to parse seat layout")
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
         let mut seats = input.clone();
-        let mut neighbour_counts = seats
-            .iter()
-            .map(|row| row.iter().map(|_| 0).collect())
-            .collect();
+        let mut neighbour_counts = Grid2D::filled(seats.width(), seats.height(), 0);
         while increment_seats(&mut seats, &mut neighbour_counts, 4, false) {}
-        let vacant = seats
-            .iter()
-            .map(|row| row.iter().filter(|&&c| c == '#').count())
-            .sum();
+        let vacant = seats.iter().filter(|(_, &c)| c == '#').count();
         (input, vacant)
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
         let mut seats = input;
-        let mut neighbour_counts = seats
-            .iter()
-            .map(|row| row.iter().map(|_| 0).collect())
-            .collect();
+        let mut neighbour_counts = Grid2D::filled(seats.width(), seats.height(), 0);
         while increment_seats(&mut seats, &mut neighbour_counts, 5, true) {}
-        seats
-            .iter()
-            .map(|row| row.iter().filter(|&&c| c == '#').count())
-            .sum()
+        seats.iter().filter(|(_, &c)| c == '#').count()
     }
 }
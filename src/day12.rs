@@ -13,8 +13,8 @@ fn rotate(point_x: &mut i32, point_y: &mut i32, angle: i32) {
     *point_y = new_facing_y;
 }
 
-fn final_ship_distance<'a>(
-    actions: &[(&'a str, i32)],
+fn final_ship_distance(
+    actions: &[(&str, i32)],
     mut waypoint_x: i32,
     mut waypoint_y: i32,
     move_waypoint: bool,
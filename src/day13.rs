@@ -1,25 +1,25 @@
 use crate::day::Day;
-use crate::util;
+use crate::util::modular;
 
 pub struct Day13 {}
 
 impl<'a> Day<'a> for Day13 {
-    type Input1 = (usize, Vec<Option<usize>>);
-    type Input2 = Vec<Option<usize>>;
-    type Output1 = usize;
-    type Output2 = usize;
+    type Input1 = (u64, Vec<Option<u64>>);
+    type Input2 = Vec<Option<u64>>;
+    type Output1 = u64;
+    type Output2 = u128;
 
     const INDEX: usize = 13;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
         let lines: Vec<_> = raw_input.lines().filter(|line| !line.is_empty()).collect();
         (
-            lines[0].parse::<usize>().unwrap(),
+            lines[0].parse::<u64>().unwrap(),
             lines[1]
                 .split(',')
                 .map(|s| match s {
                     "x" => None,
-                    n => Some(n.parse::<usize>().unwrap()),
+                    n => Some(n.parse::<u64>().unwrap()),
                 })
                 .collect(),
         )
@@ -40,21 +40,13 @@ impl<'a> Day<'a> for Day13 {
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
-        let congruences: Vec<_> = input
-            .iter()
-            .enumerate()
-            .filter_map(|pair| match pair {
-                (_, None) => None,
-                (idx, Some(n)) => Some(((-(idx as i32)).rem_euclid(*n as i32) as usize, *n)),
-            })
-            .collect();
-        let mut solution = 0;
-        let big_modulo: usize = congruences.iter().map(|pair| pair.1).product();
-        for (remainder, small_modulo) in congruences {
-            let zero_for_others = big_modulo / small_modulo;
-            solution += zero_for_others
-                * util::prime_divide_modular(remainder, zero_for_others, small_modulo);
+        let congruences = input.iter().enumerate().filter_map(|pair| match pair {
+            (_, None) => None,
+            (idx, Some(n)) => Some(((-(idx as i128)).rem_euclid(*n as i128) as u64, *n)),
+        });
+        match modular::chinese_remainder(congruences) {
+            Ok((solution, _)) => solution,
+            Err(err) => panic!("no timestamp fits the schedule: {}", err),
         }
-        solution % big_modulo
     }
 }
@@ -1,4 +1,9 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::parse::{
+        self, delimited, either, integer, lines, literal, map, pair, preceded, take_while1,
+    },
+};
 use std::collections::HashMap;
 
 #[derive(Copy, Clone)]
@@ -72,22 +77,20 @@ impl<'a> Day<'a> for Day14 {
     const INDEX: usize = 14;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        raw_input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| {
-                let mut parts = line.split(" = ");
-                let lhs = parts.next().unwrap();
-                let rhs = parts.next().unwrap();
-                match lhs {
-                    "mask" => Operation::SetMask(Bitmask { bits: rhs }),
-                    _mem => Operation::SetMemory(
-                        lhs[4..lhs.len() - 1].parse::<usize>().unwrap(),
-                        rhs.parse::<usize>().unwrap(),
-                    ),
-                }
-            })
-            .collect()
+        let set_mask = preceded(
+            literal("mask = "),
+            take_while1(|c| matches!(c, 'X' | '0' | '1'), "a bitmask"),
+        );
+        let set_memory = pair(
+            delimited(literal("mem["), integer(), literal("] = ")),
+            integer(),
+        );
+        let operation = either(
+            map(set_mask, |bits| Operation::SetMask(Bitmask { bits })),
+            map(set_memory, |(idx, val)| Operation::SetMemory(idx, val)),
+        );
+        parse::parse_all(lines(operation), raw_input)
+            .unwrap_or_else(|err| panic!("failed to parse program: {}", err))
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
@@ -1,5 +1,5 @@
-use crate::day::Day;
-use std::collections::HashMap;
+use crate::{day::Day, util::assignment};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
 pub struct Input<'a> {
@@ -120,7 +120,7 @@ impl<'a> Day<'a> for Day16 {
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
         let mut field_possible_positions = HashMap::new();
         for (field_name, field) in input.fields.iter() {
-            let valid_positions: Vec<_> = (0..input.fields.len())
+            let valid_positions: HashSet<_> = (0..input.fields.len())
                 .filter(|&idx| {
                     input
                         .nearby_tickets
@@ -130,20 +130,8 @@ impl<'a> Day<'a> for Day16 {
                 .collect();
             field_possible_positions.insert(field_name, valid_positions);
         }
-        let mut field_positions = HashMap::new();
-        while field_positions.len() < input.fields.len() {
-            for (field_name, possible_positions) in field_possible_positions.iter() {
-                let possibilities: Vec<_> = possible_positions
-                    .iter()
-                    .filter(|idx| field_positions.values().find(|i| i == idx).is_none())
-                    .copied()
-                    .collect();
-                if possibilities.len() == 1 {
-                    field_positions.insert(field_name, possibilities[0]);
-                    break;
-                }
-            }
-        }
+        let field_positions = assignment::assign_unique(&field_possible_positions)
+            .unwrap_or_else(|err| panic!("failed to identify ticket fields: {}", err));
         field_positions
             .into_iter()
             .filter(|name_pos| name_pos.0.starts_with("departure"))
@@ -1,9 +1,99 @@
 use crate::{
     day::Day,
-    util::{self, Coord3DWithDiagonals, Coord4DWithDiagonals},
+    util::{
+        self, BoundingBox, Coord3DWithDiagonals, Coord4DWithDiagonals, CoordND, Flags, Grid2D,
+        LifeRule,
+    },
 };
 use std::collections::HashSet;
 
+const STEPS: usize = 6;
+
+fn conway_rule() -> LifeRule {
+    LifeRule::new(&[3], &[2, 3]).unwrap()
+}
+
+fn lift_to_4d(grid: &HashSet<Coord3DWithDiagonals>) -> HashSet<Coord4DWithDiagonals> {
+    grid.iter()
+        .map(|pos| Coord4DWithDiagonals(pos.0, pos.1, pos.2, 0))
+        .collect()
+}
+
+const AXIS_NAMES: [&str; 6] = ["x", "y", "z", "w", "v", "u"];
+
+// prints each 2d slice in the same layout as the puzzle examples, framed to also cover the
+// starting region
+fn render<const N: usize>(grid: &HashSet<CoordND<N>>, start: BoundingBox<N>) {
+    let bounds =
+        BoundingBox::around(grid.iter().copied()).map_or(start, |bounds| bounds.union(start));
+    let mut slice_max = bounds.max();
+    slice_max[0] = bounds.min()[0];
+    slice_max[1] = bounds.min()[1];
+    for slice in BoundingBox::new(bounds.min(), slice_max).unwrap().points() {
+        let label: Vec<_> = (2..N)
+            .map(|axis| format!("{}={}", AXIS_NAMES[axis], slice.0[axis]))
+            .collect();
+        println!();
+        if !label.is_empty() {
+            println!("  {}", label.join(", "));
+        }
+        for y in bounds.min()[1]..=bounds.max()[1] {
+            let row: String = (bounds.min()[0]..=bounds.max()[0])
+                .map(|x| {
+                    let mut cell = slice;
+                    cell.0[0] = x;
+                    cell.0[1] = y;
+                    if grid.contains(&cell) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            println!("  {}", row);
+        }
+    }
+}
+
+// the starting slice is flat, so every axis past the first two stays mirror symmetric
+fn simulate<const N: usize>(
+    slice: &HashSet<Coord3DWithDiagonals>,
+    rule: &LifeRule,
+    steps: usize,
+    mirrored: bool,
+    show: bool,
+) -> usize {
+    let mut grid: HashSet<_> = slice
+        .iter()
+        .map(|pos| {
+            let mut coords = [0; N];
+            coords[0] = pos.0;
+            coords[1] = pos.1;
+            CoordND(coords)
+        })
+        .collect();
+    let start = BoundingBox::around(grid.iter().copied())
+        .unwrap_or_else(|| BoundingBox::new([0; N], [0; N]).unwrap());
+    if mirrored {
+        let mirror_axes: Vec<_> = (2..N).collect();
+        for _ in 1..=steps {
+            rule.step_mirrored(&mut grid, &mirror_axes);
+        }
+        if show {
+            render(&util::unmirror(&grid, &mirror_axes), start);
+        }
+        util::mirrored_population(&grid, &mirror_axes)
+    } else {
+        for _ in 1..=steps {
+            rule.step(&mut grid);
+        }
+        if show {
+            render(&grid, start);
+        }
+        grid.len()
+    }
+}
+
 pub struct Day17 {}
 
 impl<'a> Day<'a> for Day17 {
@@ -15,38 +105,63 @@ impl<'a> Day<'a> for Day17 {
     const INDEX: usize = 17;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        let mut grid = HashSet::new();
-        for (y, line) in raw_input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .enumerate()
-        {
-            for (x, c) in line.char_indices() {
-                if c == '#' {
-                    grid.insert(Coord3DWithDiagonals(x as i32, y as i32, 0));
-                }
-            }
-        }
-        grid
+        let slice = Grid2D::parse(raw_input, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .expect("failed to parse slice");
+        slice
+            .iter()
+            .filter(|(_, &active)| active)
+            .map(|((x, y), _)| Coord3DWithDiagonals(x as i32, y as i32, 0))
+            .collect()
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
+        let rule = conway_rule();
         let mut grid = input.clone();
-        for _ in 1..=6 {
-            util::step_cellular_automata(&mut grid, |n| n == 3, |n| (n != 2 && n != 3));
+        for _ in 1..=STEPS {
+            rule.step(&mut grid);
         }
-        let grid4d = input
-            .iter()
-            .map(|pos| Coord4DWithDiagonals(pos.0, pos.1, pos.2, 0))
-            .collect();
-        (grid4d, grid.len())
+        (lift_to_4d(&input), grid.len())
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
+        let rule = conway_rule();
         let mut grid = input;
-        for _ in 1..=6 {
-            util::step_cellular_automata(&mut grid, |n| n == 3, |n| (n != 2 && n != 3));
+        for _ in 1..=STEPS {
+            rule.step(&mut grid);
         }
         grid.len()
     }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(
+            args,
+            &["--rule", "--steps", "--dims", "--mirrored", "--render"],
+        )?;
+        let rule = flags.get("--rule")?.unwrap_or_else(conway_rule);
+        let steps = flags.get("--steps")?.unwrap_or(STEPS);
+        let dims = flags
+            .get("--dims")?
+            .map_or_else(|| vec![3, 4], |dims| vec![dims]);
+        let mirrored = flags.has("--mirrored");
+        let show = flags.has("--render");
+        let slice = Self::parse(raw_input);
+        println!();
+        println!("day{:02} ({} for {} steps):", Self::INDEX, rule, steps);
+        for dim in dims {
+            let count = match dim {
+                2 => simulate::<2>(&slice, &rule, steps, mirrored, show),
+                3 => simulate::<3>(&slice, &rule, steps, mirrored, show),
+                4 => simulate::<4>(&slice, &rule, steps, mirrored, show),
+                5 => simulate::<5>(&slice, &rule, steps, mirrored, show),
+                6 => simulate::<6>(&slice, &rule, steps, mirrored, show),
+                _ => return Err(format!("unsupported dimension {}, expected 2 to 6", dim)),
+            };
+            println!("  {}d: {}", dim, count);
+        }
+        Ok(())
+    }
 }
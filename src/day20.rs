@@ -1,322 +1,168 @@
-use crate::{day::Day, util};
-use itertools::iproduct;
-use std::{
-    collections::{HashMap, HashSet},
-    convert::TryInto,
+use crate::{
+    day::Day,
+    util::{
+        parse::{self, blocks, delimited, integer, line_ending, literal, pair, rest, terminated},
+        BoundingBox, CoordND, Flags, Grid2D,
+    },
 };
+use itertools::iproduct;
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug)]
-pub struct Tile {
-    id: usize,
-    data: [[bool; 10]; 10],
-}
+const SEA_MONSTER: &str = concat!(
+    "                  # \n",
+    "#    ##    ##    ###\n",
+    " #  #  #  #  #  #   \n",
+);
 
-impl Tile {
-    fn orientations(&self) -> Orientations<'_> {
-        Orientations {
-            tile: self,
-            next_rotate: 0,
-            next_flip: 0,
-        }
+fn parse_pixel(c: char) -> Option<bool> {
+    match c {
+        '#' => Some(true),
+        '.' => Some(false),
+        _ => None,
     }
 }
 
-#[derive(Clone, Copy)]
-struct OrientedTile<'a> {
-    tile: &'a Tile,
-    rotate: usize,
-    flip_x: bool,
-    flip_y: bool,
-}
-
-impl<'a> OrientedTile<'a> {
-    fn cell(&self, x: usize, y: usize) -> bool {
-        let (prerot_x, prerot_y) = match self.rotate.rem_euclid(4) {
-            0 => (x, y),
-            1 => (9 - y, x),
-            2 => (9 - x, 9 - y),
-            3 => (y, 9 - x),
-            _ => unreachable!(),
-        };
-        let (x, y) = (
-            if self.flip_x { 9 - prerot_x } else { prerot_x },
-            if self.flip_y { 9 - prerot_y } else { prerot_y },
-        );
-        self.tile.data[y][x]
-    }
-
-    fn then_transform(self, rotate: usize, flip_x: bool, flip_y: bool) -> Self {
-        let mut result = self;
-        if flip_x {
-            result.rotate = 4 - result.rotate;
-            result.flip_x = !result.flip_x;
-        }
-        if flip_y {
-            result.rotate = 4 - result.rotate;
-            result.flip_y = !result.flip_y;
-        }
-        result.rotate = (result.rotate + rotate) % 4;
-        result
-    }
-
-    fn side(&self, side: usize) -> [bool; 10] {
-        match side {
-            0 => [
-                self.cell(9, 0),
-                self.cell(9, 1),
-                self.cell(9, 2),
-                self.cell(9, 3),
-                self.cell(9, 4),
-                self.cell(9, 5),
-                self.cell(9, 6),
-                self.cell(9, 7),
-                self.cell(9, 8),
-                self.cell(9, 9),
-            ],
-            1 => [
-                self.cell(0, 0),
-                self.cell(1, 0),
-                self.cell(2, 0),
-                self.cell(3, 0),
-                self.cell(4, 0),
-                self.cell(5, 0),
-                self.cell(6, 0),
-                self.cell(7, 0),
-                self.cell(8, 0),
-                self.cell(9, 0),
-            ],
-            2 => [
-                self.cell(0, 0),
-                self.cell(0, 1),
-                self.cell(0, 2),
-                self.cell(0, 3),
-                self.cell(0, 4),
-                self.cell(0, 5),
-                self.cell(0, 6),
-                self.cell(0, 7),
-                self.cell(0, 8),
-                self.cell(0, 9),
-            ],
-            3 => [
-                self.cell(0, 9),
-                self.cell(1, 9),
-                self.cell(2, 9),
-                self.cell(3, 9),
-                self.cell(4, 9),
-                self.cell(5, 9),
-                self.cell(6, 9),
-                self.cell(7, 9),
-                self.cell(8, 9),
-                self.cell(9, 9),
-            ],
-            _ => unreachable!(),
-        }
+// sides are numbered anticlockwise starting from the right
+fn side(data: &Grid2D<bool>, side: usize) -> Vec<bool> {
+    match side {
+        0 => data.column(data.width() - 1).copied().collect(),
+        1 => data.row(0).to_vec(),
+        2 => data.column(0).copied().collect(),
+        3 => data.row(data.height() - 1).to_vec(),
+        _ => unreachable!(),
     }
 }
 
-struct Orientations<'a> {
-    tile: &'a Tile,
-    next_rotate: usize,
-    next_flip: u8,
-}
+// the orientation of the image containing sea monsters, along with the pixels they cover
+type Sighting = (Grid2D<bool>, HashSet<(usize, usize)>);
 
-impl<'a> Iterator for Orientations<'a> {
-    type Item = OrientedTile<'a>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.next_rotate < 4 {
-            let item = OrientedTile {
-                tile: self.tile,
-                rotate: self.next_rotate,
-                flip_x: self.next_flip & 1 == 1,
-                flip_y: self.next_flip & 2 == 2,
-            };
-            if self.next_flip == 4 {
-                self.next_rotate += 1;
-                self.next_flip = 0;
-            } else {
-                self.next_flip += 1;
+fn find_monsters(image: &Grid2D<bool>) -> Option<Sighting> {
+    let sea_monster = Grid2D::parse(SEA_MONSTER, |c| match c {
+        '#' => Some(true),
+        ' ' => Some(false),
+        _ => None,
+    })
+    .unwrap();
+    let monster_offsets: Vec<_> = sea_monster
+        .iter()
+        .filter(|(_, &pixel)| pixel)
+        .map(|(pos, _)| pos)
+        .collect();
+    image.orientations().find_map(|(_, image)| {
+        let mut monster_tiles = HashSet::new();
+        for (x, y) in image.positions() {
+            let monster: Vec<_> = monster_offsets
+                .iter()
+                .map(|(dx, dy)| (x + dx, y + dy))
+                .collect();
+            if monster
+                .iter()
+                .all(|&(x, y)| image.get(x as isize, y as isize) == Some(&true))
+            {
+                monster_tiles.extend(monster);
             }
-            Some(item)
-        } else {
+        }
+        if monster_tiles.is_empty() {
             None
+        } else {
+            Some((image, monster_tiles))
         }
-    }
+    })
+}
+
+#[derive(Debug)]
+pub struct Tile {
+    id: usize,
+    data: Grid2D<bool>,
 }
 
 pub struct Day20 {}
 
 impl<'a> Day<'a> for Day20 {
     type Input1 = Vec<Tile>;
-    type Input2 = HashMap<(i32, i32), bool>;
+    type Input2 = Grid2D<bool>;
     type Output1 = usize;
     type Output2 = usize;
 
     const INDEX: usize = 20;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        let lines: Vec<_> = raw_input.lines().collect();
-        lines
-            .split(|line| line.is_empty())
-            .filter(|chunk| !chunk.is_empty())
-            .map(|chunk| {
-                let id_line = chunk[0];
-                let data = &chunk[1..];
-                Tile {
-                    id: id_line[5..id_line.len() - 1].parse().unwrap(),
-                    data: data
-                        .iter()
-                        .map(|line| {
-                            line.chars()
-                                .map(|c| c == '#')
-                                .collect::<Vec<_>>()
-                                .as_slice()
-                                .try_into()
-                                .unwrap()
-                        })
-                        .collect::<Vec<_>>()
-                        .as_slice()
-                        .try_into()
-                        .unwrap(),
-                }
+        let id = delimited(literal("Tile "), integer(), literal(":"));
+        let tile = pair(terminated(id, line_ending()), rest());
+        let tiles = parse::parse_all(blocks(tile), raw_input)
+            .unwrap_or_else(|err| panic!("failed to parse tiles: {}", err));
+        tiles
+            .into_iter()
+            .map(|(id, data)| Tile {
+                id,
+                data: Grid2D::parse(data, parse_pixel).expect("failed to parse tile"),
             })
             .collect()
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
-        let mut neighbours = HashMap::new();
-        for tile in input.iter() {
-            let tile_orient = tile.orientations().next().unwrap();
-            let tile_sides = [
-                tile_orient.side(0),
-                tile_orient.side(1),
-                tile_orient.side(2),
-                tile_orient.side(3),
-            ];
-            for (i, &tile_side) in tile_sides.iter().enumerate() {
-                let opposite_side = (2 + i) % 4;
-                'search: for other in input.iter() {
-                    if other.id == tile.id {
-                        continue;
-                    }
-                    for other_orient in other.orientations() {
-                        if other_orient.side(opposite_side) == tile_side {
-                            neighbours
-                                .entry(tile.id)
-                                .or_insert([None, None, None, None])[i] = Some(other_orient);
-                            break 'search;
-                        }
-                    }
-                }
-            }
-        }
+        let orientations: Vec<Vec<_>> = input
+            .iter()
+            .map(|tile| tile.data.orientations().map(|pair| pair.1).collect())
+            .collect();
         let dx = [1, 0, -1, 0];
         let dy = [0, -1, 0, 1];
         let mut grid = HashMap::new();
         let mut border = Vec::new();
-        let mut placed_ids = HashSet::new();
-        grid.insert((0, 0), input[0].orientations().next().unwrap());
-        placed_ids.insert(input[0].id);
+        let mut unplaced: HashSet<_> = (1..input.len()).collect();
+        grid.insert((0, 0), (0, input[0].data.clone()));
         border.push((0, 0));
         while let Some((x, y)) = border.pop() {
-            let placed = grid[&(x, y)];
-            for (side, neighbour) in neighbours[&placed.tile.id].iter().enumerate() {
+            for side_idx in 0..4 {
+                let neighbour_pos = (x + dx[side_idx], y + dy[side_idx]);
+                if grid.contains_key(&neighbour_pos) {
+                    continue;
+                }
+                let placed_side = side(&grid[&(x, y)].1, side_idx);
+                let opposite_side = (side_idx + 2) % 4;
+                let neighbour = unplaced.iter().find_map(|&tile_idx| {
+                    orientations[tile_idx]
+                        .iter()
+                        .find(|data| side(data, opposite_side) == placed_side)
+                        .map(|data| (tile_idx, data.clone()))
+                });
                 if let Some(neighbour) = neighbour {
-                    if !placed_ids.contains(&neighbour.tile.id) {
-                        let side = if placed.flip_x { (6 - side) % 4 } else { side };
-                        let side = if placed.flip_y { 4 - side } else { side };
-                        let side = (side + placed.rotate) % 4;
-                        let neighbour_pos = (x + dx[side], y + dy[side]);
-                        grid.insert(
-                            neighbour_pos,
-                            neighbour.then_transform(placed.rotate, placed.flip_x, placed.flip_y),
-                        );
-                        placed_ids.insert(neighbour.tile.id);
-                        border.push(neighbour_pos);
-                    }
+                    unplaced.remove(&neighbour.0);
+                    grid.insert(neighbour_pos, neighbour);
+                    border.push(neighbour_pos);
                 }
             }
         }
-        let ((x_min, x_max), (y_min, y_max)) = util::range_2d(grid.keys().cloned());
+        let bounds = BoundingBox::around(grid.keys().map(|&(x, y)| CoordND([x, y]))).unwrap();
+        let ([x_min, y_min], [x_max, y_max]) = (bounds.min(), bounds.max());
         let corner_product = iproduct!([x_min, x_max].iter(), [y_min, y_max].iter())
-            .map(|xy| grid[&(*xy.0, *xy.1)].tile.id)
+            .map(|xy| input[grid[&(*xy.0, *xy.1)].0].id)
             .product();
-        let mut new_grid = HashMap::new();
-        for ((x, y), tile) in grid.into_iter() {
-            for dy in 0..8 {
-                for dx in 0..8 {
-                    new_grid.insert(
-                        (8 * x + dx as i32, 8 * y + dy as i32),
-                        tile.cell(dx + 1, dy + 1),
-                    );
-                }
-            }
-        }
-        (new_grid, corner_product)
+        let image = Grid2D::from_fn(8 * bounds.len(0), 8 * bounds.len(1), |x, y| {
+            let tile = &grid[&(x_min + (x / 8) as i32, y_min + (y / 8) as i32)].1;
+            tile[(x % 8 + 1, y % 8 + 1)]
+        });
+        (image, corner_product)
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
-        let ((x_min, x_max), (y_min, y_max)) = util::range_2d(input.keys().cloned());
-        let mut monster_tiles = HashSet::new();
-        let sea_monster = [
-            (0, 0),
-            (1, -1),
-            (4, -1),
-            (5, 0),
-            (6, 0),
-            (7, -1),
-            (10, -1),
-            (11, 0),
-            (12, 0),
-            (13, -1),
-            (16, -1),
-            (17, 0),
-            (18, 0),
-            (18, 1),
-            (19, 0),
-        ];
-        let cos = [1, 0, -1, 0];
-        let sin = [0, -1, 0, 1];
-        let mut match_oriented = |x, y, rotate, flip| {
-            let oriented_monster: Vec<_> = sea_monster
-                .iter()
-                .map(|pair| {
-                    let (dx, dy) = pair;
-                    let (dx, dy) = (*dx, if flip { -dy } else { *dy });
-                    let (dx, dy) = (
-                        dx * cos[rotate] - dy * sin[rotate],
-                        dx * sin[rotate] + dy * cos[rotate],
-                    );
-                    (x + dx, y + dy)
-                })
-                .collect();
-            if oriented_monster.iter().all(|pos| input[&pos]) {
-                monster_tiles.extend(oriented_monster);
-            }
-        };
-        #[allow(clippy::clippy::range_minus_one)]
-        for y in y_min + 1..=y_max - 1 {
-            for x in x_min..=x_max - 19 {
-                match_oriented(x, y, 0, false);
-                match_oriented(x, y, 0, true);
-                match_oriented(x + 19, y, 2, false);
-                match_oriented(x + 19, y, 2, true);
-            }
-        }
-        for y in y_min + 19..=y_max {
-            #[allow(clippy::clippy::range_minus_one)]
-            for x in x_min + 1..=x_max - 1 {
-                match_oriented(x, y, 1, false);
-                match_oriented(x, y, 1, true);
-                match_oriented(x, y - 19, 3, false);
-                match_oriented(x, y - 19, 3, true);
+        let (_, monster_tiles) = find_monsters(&input).expect("no sea monsters found");
+        let rough_count = input.iter().filter(|(_, &pixel)| pixel).count();
+        rough_count - monster_tiles.len()
+    }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(args, &["--render"])?;
+        if flags.has("--render") {
+            let (image, _) = Self::solve_part1(Self::parse(raw_input));
+            let (image, monster_tiles) =
+                find_monsters(&image).ok_or_else(|| "no sea monsters found".to_owned())?;
+            let mut picture = image.map(|&pixel| if pixel { '#' } else { '.' });
+            for pos in monster_tiles {
+                picture[pos] = 'O';
             }
+            print!("{}", picture.render(|&c| c));
         }
-        input
-            .iter()
-            .filter(|kv| *kv.1)
-            .map(|kv| kv.0)
-            .filter(|pos| !monster_tiles.contains(pos))
-            .count()
+        Ok(())
     }
 }
@@ -1,4 +1,11 @@
-use crate::{day::Day, util::Ignore};
+use crate::{
+    day::Day,
+    util::{
+        assignment,
+        parse::{self, delimited, lines, literal, map, optional, pair, separated, word},
+        Ignore,
+    },
+};
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
@@ -19,27 +26,20 @@ impl<'a> Day<'a> for Day21 {
     const INDEX: usize = 21;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        raw_input
-            .lines()
-            .filter(|line| !line.is_empty())
-            .map(|line| {
-                let mut parts = line.split('(');
-                let ingredients = parts
-                    .next()
-                    .unwrap()
-                    .split(' ')
-                    .filter(|w| !w.is_empty())
-                    .collect();
-                let allergens = parts
-                    .next()
-                    .map(|s| s[9..s.len() - 1].split(", ").collect())
-                    .unwrap_or_else(HashSet::new);
-                Food {
-                    ingredients,
-                    allergens,
-                }
-            })
-            .collect()
+        let allergens = delimited(
+            literal(" (contains "),
+            separated(word(), literal(", ")),
+            literal(")"),
+        );
+        let food = map(
+            pair(separated(word(), literal(" ")), optional(allergens)),
+            |(ingredients, allergens)| Food {
+                ingredients: ingredients.into_iter().collect(),
+                allergens: allergens.into_iter().flatten().collect(),
+            },
+        );
+        parse::parse_all(lines(food), raw_input)
+            .unwrap_or_else(|err| panic!("failed to parse foods: {}", err))
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
@@ -69,24 +69,8 @@ impl<'a> Day<'a> for Day21 {
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
-        let mut allergen_containment_map = input;
-        let mut known_allergens: HashMap<&str, &str> = HashMap::new();
-        while known_allergens.len() < allergen_containment_map.len() {
-            for (allergen, ingredients) in allergen_containment_map.iter_mut() {
-                if known_allergens.contains_key(allergen) {
-                    continue;
-                }
-                ingredients.retain(|ingredient| {
-                    known_allergens
-                        .values()
-                        .find(|&i| i == ingredient)
-                        .is_none()
-                });
-                if ingredients.len() == 1 {
-                    known_allergens.insert(allergen, ingredients.iter().next().unwrap());
-                }
-            }
-        }
+        let known_allergens = assignment::assign_unique(&input)
+            .unwrap_or_else(|err| panic!("failed to identify allergens: {}", err));
         let mut known_allergens: Vec<_> = known_allergens.iter().collect();
         known_allergens.sort_by_key(|allergen_ingredient| allergen_ingredient.0);
         known_allergens
@@ -1,65 +1,45 @@
 use crate::{
     day::Day,
-    util::{self, Coord},
+    util::{
+        hex::{Cube, Orientation},
+        Flags, LifeRule,
+    },
 };
 use std::collections::HashSet;
 
-// invariant: x + y + z == 0
-#[derive(PartialEq, Eq, Clone, Copy, Hash)]
-pub struct HexCoord(i32, i32, i32);
+const DAYS: usize = 100;
 
-impl Coord for HexCoord {
-    fn for_neighbours<F: FnMut(Self)>(&self, mut f: F) {
-        f(Self(self.0, self.1 + 1, self.2 - 1));
-        f(Self(self.0, self.1 - 1, self.2 + 1));
-        f(Self(self.0 - 1, self.1 + 1, self.2));
-        f(Self(self.0 - 1, self.1, self.2 + 1));
-        f(Self(self.0 + 1, self.1 - 1, self.2));
-        f(Self(self.0 + 1, self.1, self.2 - 1));
-    }
+fn flip_rule() -> LifeRule {
+    LifeRule::new(&[2], &[1, 2]).unwrap()
+}
+
+fn parse_tiles(raw_input: &str, orientation: Orientation) -> Result<Vec<Cube>, String> {
+    raw_input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let steps = orientation.parse_directions(line)?;
+            Ok(steps
+                .into_iter()
+                .fold(Cube::ORIGIN, |tile, step| tile + step))
+        })
+        .collect()
 }
 
 pub struct Day24 {}
 
 impl<'a> Day<'a> for Day24 {
-    type Input1 = Vec<HexCoord>;
-    type Input2 = HashSet<HexCoord>;
+    type Input1 = Vec<Cube>;
+    type Input2 = HashSet<Cube>;
     type Output1 = usize;
     type Output2 = usize;
 
     const INDEX: usize = 24;
 
     fn parse(raw_input: &'a str) -> Self::Input1 {
-        raw_input
-            .lines()
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty())
-            .map(|mut line| {
-                let mut cursor = HexCoord(0, 0, 0);
-                while !line.is_empty() {
-                    let (skip, dir) = match &line[0..1] {
-                        "n" => match &line[1..2] {
-                            "e" => (2, (0, 1, -1)),
-                            "w" => (2, (1, 0, -1)),
-                            _ => unreachable!(),
-                        },
-                        "s" => match &line[1..2] {
-                            "e" => (2, (-1, 0, 1)),
-                            "w" => (2, (0, -1, 1)),
-                            _ => unreachable!(),
-                        },
-                        "e" => (1, (-1, 1, 0)),
-                        "w" => (1, (1, -1, 0)),
-                        _ => unreachable!(),
-                    };
-                    cursor.0 += dir.0;
-                    cursor.1 += dir.1;
-                    cursor.2 += dir.2;
-                    line = &line[skip..];
-                }
-                cursor
-            })
-            .collect()
+        parse_tiles(raw_input, Orientation::PointyTop)
+            .unwrap_or_else(|err| panic!("failed to parse tiles: {}", err))
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
@@ -74,10 +54,49 @@ impl<'a> Day<'a> for Day24 {
     }
 
     fn solve_part2(input: Self::Input2) -> Self::Output2 {
+        let rule = flip_rule();
         let mut grid = input;
-        for _ in 1..=100 {
-            util::step_cellular_automata(&mut grid, |n| n == 2, |n| (n != 1 && n != 2));
+        for _ in 1..=DAYS {
+            rule.step(&mut grid);
         }
         grid.len()
     }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(args, &["--rule", "--steps", "--flat-top", "--render"])?;
+        let orientation = if flags.has("--flat-top") {
+            Orientation::FlatTop
+        } else {
+            Orientation::PointyTop
+        };
+        let rule = flags.get("--rule")?.unwrap_or_else(flip_rule);
+        let steps = flags.get("--steps")?.unwrap_or(DAYS);
+        let (mut grid, black_count) = Self::solve_part1(parse_tiles(raw_input, orientation)?);
+        let initial_grid = grid.clone();
+        for _ in 1..=steps {
+            rule.step(&mut grid);
+        }
+        println!();
+        println!("day{:02} ({} for {} steps):", Self::INDEX, rule, steps);
+        println!("  initial: {}", black_count);
+        println!("  final: {}", grid.len());
+        if let Some(furthest) = grid.iter().map(|&tile| tile.distance(Cube::ORIGIN)).max() {
+            println!("  furthest from the reference tile: {}", furthest);
+        }
+        if flags.has("--render") {
+            let frame = match (orientation.bounds(&initial_grid), orientation.bounds(&grid)) {
+                (Some(initial), Some(last)) => initial.union(last),
+                (Some(bounds), None) | (None, Some(bounds)) => bounds,
+                (None, None) => return Ok(()),
+            };
+            let frame = frame.expanded(1).unwrap();
+            for world in [&initial_grid, &grid] {
+                println!();
+                for line in orientation.render(world, frame).lines() {
+                    println!("  {}", line);
+                }
+            }
+        }
+        Ok(())
+    }
 }
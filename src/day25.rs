@@ -1,11 +1,37 @@
-use crate::{day::Day, util};
+use crate::{
+    day::Day,
+    util::{
+        modular::{self, ModError, ModInt},
+        Flags,
+    },
+};
+
+const SUBJECT: u64 = 7;
+const PRIME: u64 = 20201227;
+
+fn encryption_key(
+    door_public: u64,
+    card_public: u64,
+    subject: u64,
+    modulus: u64,
+    pohlig_hellman: bool,
+) -> Result<u64, ModError> {
+    let door_loop_size = if pohlig_hellman {
+        modular::discrete_log_prime(subject, door_public, modulus)?
+    } else {
+        modular::discrete_log(subject, door_public, modulus)?
+    };
+    Ok(ModInt::new(card_public, modulus)
+        .pow(door_loop_size)
+        .value())
+}
 
 pub struct Day25 {}
 
 impl<'a> Day<'a> for Day25 {
-    type Input1 = (usize, usize);
+    type Input1 = (u64, u64);
     type Input2 = ();
-    type Output1 = usize;
+    type Output1 = u64;
     type Output2 = &'static str;
 
     const INDEX: usize = 25;
@@ -18,22 +44,39 @@ impl<'a> Day<'a> for Day25 {
     }
 
     fn solve_part1(input: Self::Input1) -> (Self::Input2, Self::Output1) {
-        const PRIME: usize = 20201227;
         let (door_public, card_public) = input;
-        let mut public_key = 1;
-        for loopsize in 1.. {
-            public_key *= 7;
-            public_key %= PRIME;
-            if public_key == door_public {
-                return ((), util::exp_modular(card_public, loopsize, PRIME));
-            } else if public_key == card_public {
-                return ((), util::exp_modular(door_public, loopsize, PRIME));
-            }
-        }
-        unreachable!()
+        let key = encryption_key(door_public, card_public, SUBJECT, PRIME, true)
+            .expect("failed to find the door's loop size");
+        ((), key)
     }
 
     fn solve_part2(_input: Self::Input2) -> Self::Output2 {
         "Go click that button ;)"
     }
+
+    fn solve_with_args(raw_input: &'a str, args: &[String]) -> Result<(), String> {
+        let flags = Flags::parse(args, &["--subject", "--prime", "--bsgs"])?;
+        let subject = flags.get("--subject")?.unwrap_or(SUBJECT);
+        let prime = flags.get("--prime")?.unwrap_or(PRIME);
+        // plain baby-step giant-step doesn't rely on the modulus being prime
+        let pohlig_hellman = !flags.has("--bsgs");
+        if pohlig_hellman && (prime < 2 || modular::factorize(prime) != [(prime, 1)]) {
+            return Err(format!(
+                "expected a prime modulus, got {} (pass --bsgs to allow others)",
+                prime
+            ));
+        }
+        let (door_public, card_public) = Self::parse(raw_input);
+        let key = encryption_key(door_public, card_public, subject, prime, pohlig_hellman)
+            .map_err(|err| format!("failed to find the door's loop size: {}", err))?;
+        println!();
+        println!(
+            "day{:02} (subject {} modulo {}):",
+            Self::INDEX,
+            subject,
+            prime
+        );
+        println!("  encryption key: {}", key);
+        Ok(())
+    }
 }
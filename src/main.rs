@@ -60,44 +60,50 @@ use day24::Day24;
 use day25::Day25;
 
 macro_rules! solve {
-    ($day:ident) => {{
+    ($day:ident, $args:expr) => {{
         let mut input_file = File::open(format!("res/day{}.in", $day::INDEX))?;
         let mut input_string = String::new();
         input_file.read_to_string(&mut input_string)?;
-        $day::solve_and_print(&input_string);
+        if $args.is_empty() {
+            $day::solve_and_print(&input_string);
+        } else if let Err(err) = $day::solve_with_args(&input_string, $args) {
+            eprintln!("{}", err);
+        }
     }};
 }
 
 fn main() -> io::Result<()> {
-    match env::args().skip(1).next() {
+    let args: Vec<String> = env::args().collect();
+    let extra_args = args.get(2..).unwrap_or(&[]);
+    match args.get(1) {
         None => eprintln!("expected an argument to choose which day to solve"),
         Some(day) => match day.parse::<usize>() {
             Err(err) => eprintln!("unrecognized day \"{}\": {}", day, err),
-            Ok(1) => solve!(Day01),
-            Ok(2) => solve!(Day02),
-            Ok(3) => solve!(Day03),
-            Ok(4) => solve!(Day04),
-            Ok(5) => solve!(Day05),
-            Ok(6) => solve!(Day06),
-            Ok(7) => solve!(Day07),
-            Ok(8) => solve!(Day08),
-            Ok(9) => solve!(Day09),
-            Ok(10) => solve!(Day10),
-            Ok(11) => solve!(Day11),
-            Ok(12) => solve!(Day12),
-            Ok(13) => solve!(Day13),
-            Ok(14) => solve!(Day14),
-            Ok(15) => solve!(Day15),
-            Ok(16) => solve!(Day16),
-            Ok(17) => solve!(Day17),
-            Ok(18) => solve!(Day18),
-            Ok(19) => solve!(Day19),
-            Ok(20) => solve!(Day20),
-            Ok(21) => solve!(Day21),
-            Ok(22) => solve!(Day22),
-            Ok(23) => solve!(Day23),
-            Ok(24) => solve!(Day24),
-            Ok(25) => solve!(Day25),
+            Ok(1) => solve!(Day01, extra_args),
+            Ok(2) => solve!(Day02, extra_args),
+            Ok(3) => solve!(Day03, extra_args),
+            Ok(4) => solve!(Day04, extra_args),
+            Ok(5) => solve!(Day05, extra_args),
+            Ok(6) => solve!(Day06, extra_args),
+            Ok(7) => solve!(Day07, extra_args),
+            Ok(8) => solve!(Day08, extra_args),
+            Ok(9) => solve!(Day09, extra_args),
+            Ok(10) => solve!(Day10, extra_args),
+            Ok(11) => solve!(Day11, extra_args),
+            Ok(12) => solve!(Day12, extra_args),
+            Ok(13) => solve!(Day13, extra_args),
+            Ok(14) => solve!(Day14, extra_args),
+            Ok(15) => solve!(Day15, extra_args),
+            Ok(16) => solve!(Day16, extra_args),
+            Ok(17) => solve!(Day17, extra_args),
+            Ok(18) => solve!(Day18, extra_args),
+            Ok(19) => solve!(Day19, extra_args),
+            Ok(20) => solve!(Day20, extra_args),
+            Ok(21) => solve!(Day21, extra_args),
+            Ok(22) => solve!(Day22, extra_args),
+            Ok(23) => solve!(Day23, extra_args),
+            Ok(24) => solve!(Day24, extra_args),
+            Ok(25) => solve!(Day25, extra_args),
             _ => todo!(),
         },
     }
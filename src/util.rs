@@ -1,7 +1,21 @@
+pub mod assignment;
+pub mod bounds;
+pub mod graph;
+pub mod grid;
+pub mod hex;
+pub mod modular;
+pub mod parse;
+pub mod pattern;
+
+pub use bounds::BoundingBox;
+pub use grid::Grid2D;
+
+use itertools::Itertools;
 use std::{
-    cmp::Ord,
     collections::{HashMap, HashSet},
+    fmt,
     hash::Hash,
+    str::FromStr,
 };
 
 pub trait Ignore {
@@ -38,51 +52,47 @@ macro_rules! set(
      };
 );
 
-pub fn exp_modular(base: usize, exp: usize, modulo: usize) -> usize {
-    match exp {
-        0 => 1,
-        1 => base % modulo,
-        exp => {
-            let root = exp_modular(base, exp / 2, modulo);
-            if exp % 2 == 0 {
-                (root * root) % modulo
-            } else {
-                ((base % modulo) * ((root * root) % modulo)) % modulo
+pub struct Flags<'a> {
+    values: HashMap<&'a str, Option<&'a str>>,
+}
+
+impl<'a> Flags<'a> {
+    pub fn parse(args: &'a [String], known: &[&str]) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        let mut args = args.iter().map(String::as_str).peekable();
+        while let Some(flag) = args.next() {
+            if !known.contains(&flag) {
+                return Err(format!(
+                    "unrecognized argument \"{}\", expected one of: {}",
+                    flag,
+                    known.join(", ")
+                ));
+            }
+            let value = args.next_if(|arg| !arg.starts_with("--"));
+            if values.insert(flag, value).is_some() {
+                return Err(format!("argument \"{}\" given more than once", flag));
             }
         }
+        Ok(Self { values })
     }
-}
 
-pub fn prime_divide_modular(num: usize, denom: usize, modulo: usize) -> usize {
-    ((num % modulo) * exp_modular(denom, modulo - 2, modulo)) % modulo
-}
+    pub fn has(&self, flag: &str) -> bool {
+        self.values.contains_key(flag)
+    }
 
-pub fn range_2d<T: Ord>(range: impl Iterator<Item = (T, T)>) -> ((T, T), (T, T))
-where
-    T: Copy,
-{
-    let mut x_min = None;
-    let mut x_max = None;
-    let mut y_min = None;
-    let mut y_max = None;
-    for (tx, ty) in range {
-        if x_min.is_none() || x_min.unwrap() > tx {
-            x_min = Some(tx);
-        }
-        if x_max.is_none() || x_max.unwrap() < tx {
-            x_max = Some(tx);
-        }
-        if y_min.is_none() || y_min.unwrap() > ty {
-            y_min = Some(ty);
-        }
-        if y_max.is_none() || y_max.unwrap() < ty {
-            y_max = Some(ty);
+    pub fn get<T: FromStr>(&self, flag: &str) -> Result<Option<T>, String>
+    where
+        T::Err: fmt::Display,
+    {
+        match self.values.get(flag) {
+            None => Ok(None),
+            Some(None) => Err(format!("expected a value after \"{}\"", flag)),
+            Some(Some(value)) => value
+                .parse()
+                .map(Some)
+                .map_err(|err| format!("invalid value \"{}\" for \"{}\": {}", value, flag, err)),
         }
     }
-    (
-        (x_min.unwrap(), x_max.unwrap()),
-        (y_min.unwrap(), y_max.unwrap()),
-    )
 }
 
 pub trait Coord: Sized {
@@ -104,6 +114,83 @@ pub fn step_cellular_automata<T, BirthFn, DeathFn>(
             *neighbour_counts.entry(neighbour).or_insert(0) += 1;
         });
     }
+    apply_neighbour_counts(alive, &neighbour_counts, birth, death);
+}
+
+// alive only holds cells with non-negative coordinates on the mirror axes, each standing for
+// its reflections across them, and neighbour counts are weighted to match the full world
+pub fn step_mirrored_automata<const N: usize, BirthFn, DeathFn>(
+    alive: &mut HashSet<CoordND<N>>,
+    mirror_axes: &[usize],
+    birth: BirthFn,
+    death: DeathFn,
+) where
+    BirthFn: Fn(usize) -> bool,
+    DeathFn: Fn(usize) -> bool,
+{
+    let mut neighbour_counts: HashMap<CoordND<N>, usize> = HashMap::new();
+    for cell in alive.iter() {
+        cell.for_neighbours(|neighbour| {
+            let mut weight = 1;
+            for &axis in mirror_axes {
+                match (cell.0[axis], neighbour.0[axis]) {
+                    (0, -1) => return,
+                    (1, 0) => weight *= 2,
+                    _ => (),
+                }
+            }
+            *neighbour_counts.entry(neighbour).or_insert(0) += weight;
+        });
+    }
+    apply_neighbour_counts(alive, &neighbour_counts, birth, death);
+}
+
+pub fn mirrored_population<const N: usize>(
+    alive: &HashSet<CoordND<N>>,
+    mirror_axes: &[usize],
+) -> usize {
+    alive
+        .iter()
+        .map(|cell| {
+            1 << mirror_axes
+                .iter()
+                .filter(|&&axis| cell.0[axis] != 0)
+                .count()
+        })
+        .sum()
+}
+
+// the full world described by the non-negative half kept by step_mirrored_automata
+pub fn unmirror<const N: usize>(
+    alive: &HashSet<CoordND<N>>,
+    mirror_axes: &[usize],
+) -> HashSet<CoordND<N>> {
+    let mut full = alive.clone();
+    for &axis in mirror_axes {
+        let reflections: Vec<_> = full
+            .iter()
+            .filter(|cell| cell.0[axis] != 0)
+            .map(|cell| {
+                let mut reflection = *cell;
+                reflection.0[axis] = -reflection.0[axis];
+                reflection
+            })
+            .collect();
+        full.extend(reflections);
+    }
+    full
+}
+
+fn apply_neighbour_counts<T, BirthFn, DeathFn>(
+    alive: &mut HashSet<T>,
+    neighbour_counts: &HashMap<T, usize>,
+    birth: BirthFn,
+    death: DeathFn,
+) where
+    T: Eq + Hash + Copy,
+    BirthFn: Fn(usize) -> bool,
+    DeathFn: Fn(usize) -> bool,
+{
     let to_add: Vec<T> = neighbour_counts
         .iter()
         .filter(|pair| !alive.contains(pair.0))
@@ -115,6 +202,171 @@ pub fn step_cellular_automata<T, BirthFn, DeathFn>(
     alive.extend(to_add);
 }
 
+// neighbour counts are stored as bitmasks, so only counts below 128 can be named in a rule
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct LifeRule {
+    birth: u128,
+    survival: u128,
+}
+
+impl LifeRule {
+    pub fn new(birth: &[usize], survival: &[usize]) -> Result<Self, String> {
+        let mask = |counts: &[usize]| {
+            counts
+                .iter()
+                .try_fold(0, |mask, &n| add_neighbour_count(mask, n))
+        };
+        Self::from_masks(mask(birth)?, mask(survival)?)
+    }
+
+    // only cells next to live ones are visited, so there's no way to birth a cell with none
+    fn from_masks(birth: u128, survival: u128) -> Result<Self, String> {
+        if birth & 1 != 0 {
+            return Err("rules with births on 0 neighbours aren't supported".to_string());
+        }
+        Ok(Self { birth, survival })
+    }
+
+    pub fn births(&self, neighbours: usize) -> bool {
+        neighbours < 128 && self.birth & (1 << neighbours) != 0
+    }
+
+    pub fn survives(&self, neighbours: usize) -> bool {
+        neighbours < 128 && self.survival & (1 << neighbours) != 0
+    }
+
+    pub fn step<T>(&self, alive: &mut HashSet<T>)
+    where
+        T: Coord + Eq + Hash + Copy,
+    {
+        step_cellular_automata(alive, |n| self.births(n), |n| !self.survives(n));
+    }
+
+    pub fn step_mirrored<const N: usize>(
+        &self,
+        alive: &mut HashSet<CoordND<N>>,
+        mirror_axes: &[usize],
+    ) {
+        step_mirrored_automata(
+            alive,
+            mirror_axes,
+            |n| self.births(n),
+            |n| !self.survives(n),
+        );
+    }
+}
+
+fn add_neighbour_count(mask: u128, n: usize) -> Result<u128, String> {
+    if n < 128 {
+        Ok(mask | 1 << n)
+    } else {
+        Err(format!("neighbour count {} is too large", n))
+    }
+}
+
+// "B36" style single digits, or "B3,10-12" style lists for larger neighbourhoods
+fn parse_neighbour_counts(counts: &str) -> Result<u128, String> {
+    let mut mask = 0;
+    let mut set = |n: usize| {
+        mask = add_neighbour_count(mask, n)?;
+        Ok::<_, String>(())
+    };
+    let parse_count = |n: &str| {
+        n.parse::<usize>()
+            .map_err(|_| format!("invalid neighbour count \"{}\"", n))
+    };
+    if counts.contains(&[',', '-'][..]) {
+        for part in counts.split(',') {
+            let mut bounds = part.splitn(2, '-');
+            let min = parse_count(bounds.next().unwrap())?;
+            let max = bounds.next().map(parse_count).transpose()?.unwrap_or(min);
+            if min > max {
+                return Err(format!("invalid neighbour count range \"{}\"", part));
+            }
+            for n in min..=max {
+                set(n)?;
+            }
+        }
+    } else {
+        for c in counts.chars() {
+            let n = c
+                .to_digit(10)
+                .ok_or_else(|| format!("invalid neighbour count \"{}\"", c))?;
+            set(n as usize)?;
+        }
+    }
+    Ok(mask)
+}
+
+fn fmt_neighbour_counts(mask: u128, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let counts: Vec<_> = (0..128).filter(|n| mask & (1 << n) != 0).collect();
+    if counts.iter().all(|&n| n < 10) {
+        counts.iter().try_for_each(|n| write!(f, "{}", n))
+    } else {
+        write!(f, "{}", counts.iter().join(","))
+    }
+}
+
+impl FromStr for LifeRule {
+    type Err = String;
+
+    fn from_str(notation: &str) -> Result<Self, Self::Err> {
+        let mut birth = None;
+        let mut survival = None;
+        for part in notation.trim().split('/') {
+            match part.chars().next() {
+                Some('B') | Some('b') if birth.is_none() => {
+                    birth = Some(parse_neighbour_counts(&part[1..])?)
+                }
+                Some('S') | Some('s') if survival.is_none() => {
+                    survival = Some(parse_neighbour_counts(&part[1..])?)
+                }
+                _ => {
+                    return Err(format!(
+                        "invalid rule \"{}\", expected e.g. B3/S23",
+                        notation
+                    ))
+                }
+            }
+        }
+        match (birth, survival) {
+            (Some(birth), Some(survival)) => Self::from_masks(birth, survival),
+            _ => Err(format!(
+                "invalid rule \"{}\", expected e.g. B3/S23",
+                notation
+            )),
+        }
+    }
+}
+
+impl fmt::Display for LifeRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B")?;
+        fmt_neighbour_counts(self.birth, f)?;
+        write!(f, "/S")?;
+        fmt_neighbour_counts(self.survival, f)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+pub struct CoordND<const N: usize>(pub [i32; N]);
+
+impl<const N: usize> Coord for CoordND<N> {
+    fn for_neighbours<F: FnMut(Self)>(&self, mut f: F) {
+        for offset in 0..3usize.pow(N as u32) {
+            let mut neighbour = *self;
+            let mut digits = offset;
+            for coord in neighbour.0.iter_mut() {
+                *coord += (digits % 3) as i32 - 1;
+                digits /= 3;
+            }
+            if neighbour != *self {
+                f(neighbour);
+            }
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Coord3DWithDiagonals(pub i32, pub i32, pub i32);
 
@@ -0,0 +1,150 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::Hash,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssignmentError<K> {
+    // no way to give these keys distinct values
+    Impossible(Vec<K>),
+    // these keys could be given different values without breaking any constraint
+    Ambiguous(Vec<K>),
+}
+
+impl<K: fmt::Debug> fmt::Display for AssignmentError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Impossible(keys) => write!(f, "no distinct values are left for {:?}", keys),
+            Self::Ambiguous(keys) => write!(f, "more than one assignment fits {:?}", keys),
+        }
+    }
+}
+
+// Kuhn's augmenting path search, ignoring the edge in banned
+fn augment(
+    key: usize,
+    edges: &[Vec<usize>],
+    banned: Option<(usize, usize)>,
+    owners: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for &value in edges[key].iter() {
+        if banned == Some((key, value)) || visited[value] {
+            continue;
+        }
+        visited[value] = true;
+        let free = match owners[value] {
+            None => true,
+            Some(owner) => augment(owner, edges, banned, owners, visited),
+        };
+        if free {
+            owners[value] = Some(key);
+            return true;
+        }
+    }
+    false
+}
+
+// gives each key a distinct value out of its candidates, requiring the choice to be unique
+pub fn assign_unique<K, V>(
+    candidates: &HashMap<K, HashSet<V>>,
+) -> Result<HashMap<K, V>, AssignmentError<K>>
+where
+    K: Clone + Eq + Hash,
+    V: Clone + Eq + Hash,
+{
+    let keys: Vec<&K> = candidates.keys().collect();
+    let values: Vec<&V> = candidates
+        .values()
+        .flatten()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let value_ids: HashMap<&V, usize> = values.iter().enumerate().map(|(i, v)| (*v, i)).collect();
+    let edges: Vec<Vec<usize>> = keys
+        .iter()
+        .map(|key| candidates[key].iter().map(|v| value_ids[v]).collect())
+        .collect();
+
+    // unit propagation handles the common case where some key always has only one option left
+    let mut owners = vec![None; values.len()];
+    let mut assigned = vec![false; keys.len()];
+    let mut progress = true;
+    while progress {
+        progress = false;
+        for key in 0..keys.len() {
+            if assigned[key] {
+                continue;
+            }
+            let mut remaining = edges[key].iter().filter(|&&value| owners[value].is_none());
+            match (remaining.next(), remaining.next()) {
+                (None, _) => return Err(AssignmentError::Impossible(vec![keys[key].clone()])),
+                (Some(&value), None) => {
+                    owners[value] = Some(key);
+                    assigned[key] = true;
+                    progress = true;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // otherwise fall back to a full matching over what's left
+    let residual: Vec<usize> = (0..keys.len()).filter(|&key| !assigned[key]).collect();
+    let residual_edges: Vec<Vec<usize>> = edges
+        .iter()
+        .map(|key_edges| {
+            key_edges
+                .iter()
+                .copied()
+                .filter(|&value| owners[value].is_none())
+                .collect()
+        })
+        .collect();
+    let unmatched: Vec<K> = residual
+        .iter()
+        .filter(|&&key| {
+            !augment(
+                key,
+                &residual_edges,
+                None,
+                &mut owners,
+                &mut vec![false; values.len()],
+            )
+        })
+        .map(|&key| keys[key].clone())
+        .collect();
+    if !unmatched.is_empty() {
+        return Err(AssignmentError::Impossible(unmatched));
+    }
+    let mut matching = vec![0; keys.len()];
+    for (value, owner) in owners.iter().enumerate() {
+        if let Some(owner) = owner {
+            matching[*owner] = value;
+        }
+    }
+    let undetermined: Vec<K> = residual
+        .iter()
+        .filter(|&&key| {
+            let mut alternative = owners.clone();
+            alternative[matching[key]] = None;
+            augment(
+                key,
+                &residual_edges,
+                Some((key, matching[key])),
+                &mut alternative,
+                &mut vec![false; values.len()],
+            )
+        })
+        .map(|&key| keys[key].clone())
+        .collect();
+    if !undetermined.is_empty() {
+        return Err(AssignmentError::Ambiguous(undetermined));
+    }
+    Ok(keys
+        .into_iter()
+        .zip(matching)
+        .map(|(key, value)| (key.clone(), values[value].clone()))
+        .collect())
+}
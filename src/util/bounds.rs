@@ -0,0 +1,90 @@
+use super::CoordND;
+
+// inclusive on both ends, so a box always holds at least one point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox<const N: usize> {
+    min: [i32; N],
+    max: [i32; N],
+}
+
+impl<const N: usize> BoundingBox<N> {
+    pub fn new(min: [i32; N], max: [i32; N]) -> Option<Self> {
+        if min.iter().zip(max.iter()).all(|pair| pair.0 <= pair.1) {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    pub fn around(points: impl IntoIterator<Item = CoordND<N>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounds = Self {
+            min: first.0,
+            max: first.0,
+        };
+        for point in points {
+            bounds = bounds.including(point);
+        }
+        Some(bounds)
+    }
+
+    pub fn min(&self) -> [i32; N] {
+        self.min
+    }
+
+    pub fn max(&self) -> [i32; N] {
+        self.max
+    }
+
+    pub fn len(&self, axis: usize) -> usize {
+        (self.max[axis] - self.min[axis] + 1) as usize
+    }
+
+    pub fn contains(&self, point: CoordND<N>) -> bool {
+        (0..N).all(|axis| self.min[axis] <= point.0[axis] && point.0[axis] <= self.max[axis])
+    }
+
+    pub fn including(mut self, point: CoordND<N>) -> Self {
+        for axis in 0..N {
+            self.min[axis] = self.min[axis].min(point.0[axis]);
+            self.max[axis] = self.max[axis].max(point.0[axis]);
+        }
+        self
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        self.including(CoordND(other.min))
+            .including(CoordND(other.max))
+    }
+
+    // a negative margin shrinks the box, giving None once nothing is left
+    pub fn expanded(mut self, margin: i32) -> Option<Self> {
+        for axis in 0..N {
+            self.min[axis] -= margin;
+            self.max[axis] += margin;
+        }
+        Self::new(self.min, self.max)
+    }
+
+    // the contained points, varying the first axis fastest
+    pub fn points(self) -> impl Iterator<Item = CoordND<N>> {
+        let mut next = Some(self.min);
+        std::iter::from_fn(move || {
+            let point = next?;
+            let mut successor = point;
+            next = (0..N)
+                .find(|&axis| {
+                    if successor[axis] < self.max[axis] {
+                        successor[axis] += 1;
+                        true
+                    } else {
+                        successor[axis] = self.min[axis];
+                        false
+                    }
+                })
+                .map(|_| successor);
+            Some(CoordND(point))
+        })
+    }
+}
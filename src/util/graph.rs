@@ -0,0 +1,222 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    hash::Hash,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError<N> {
+    UnknownNode(N),
+    Cycle(Vec<N>),
+    Overflow,
+}
+
+impl<N: fmt::Debug> fmt::Display for GraphError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownNode(node) => write!(f, "unknown node {:?}", node),
+            Self::Cycle(nodes) => write!(f, "found a cycle through {:?}", nodes),
+            Self::Overflow => write!(f, "result doesn't fit in a usize"),
+        }
+    }
+}
+
+// a weighted digraph that also keeps its edges reversed, so it can be walked either way
+#[derive(Debug, Clone)]
+pub struct Graph<N, W> {
+    nodes: Vec<N>,
+    ids: HashMap<N, usize>,
+    edges: Vec<Vec<(usize, W)>>,
+    reverse_edges: Vec<Vec<(usize, W)>>,
+}
+
+impl<N, W> Graph<N, W>
+where
+    N: Copy + Eq + Hash,
+    W: Copy,
+{
+    pub fn new() -> Self {
+        Self {
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            edges: Vec::new(),
+            reverse_edges: Vec::new(),
+        }
+    }
+
+    pub fn add_node(&mut self, node: N) -> usize {
+        let next_id = self.nodes.len();
+        let id = *self.ids.entry(node).or_insert(next_id);
+        if id == next_id {
+            self.nodes.push(node);
+            self.edges.push(Vec::new());
+            self.reverse_edges.push(Vec::new());
+        }
+        id
+    }
+
+    pub fn add_edge(&mut self, from: N, to: N, weight: W) {
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        self.edges[from].push((to, weight));
+        self.reverse_edges[to].push((from, weight));
+    }
+
+    pub fn contains(&self, node: N) -> bool {
+        self.ids.contains_key(&node)
+    }
+
+    // nodes at the start of some non-empty path to end
+    pub fn reaching(&self, end: N) -> Result<HashSet<N>, GraphError<N>> {
+        self.search(&self.reverse_edges, end)
+    }
+
+    // every node comes before its successors
+    pub fn topological_sort(&self) -> Result<Vec<N>, GraphError<N>> {
+        let mut done = vec![false; self.nodes.len()];
+        let mut order = Vec::with_capacity(self.nodes.len());
+        for root in 0..self.nodes.len() {
+            order.extend(self.postorder(root, &mut done)?);
+        }
+        Ok(order.into_iter().rev().map(|id| self.nodes[id]).collect())
+    }
+
+    pub fn find_cycle(&self) -> Option<Vec<N>> {
+        match self.topological_sort() {
+            Err(GraphError::Cycle(cycle)) => Some(cycle),
+            _ => None,
+        }
+    }
+
+    pub fn count_paths(&self, start: N, end: N) -> Result<usize, GraphError<N>> {
+        let end = self.id(end)?;
+        self.path_sum(start, |id, successor_sums| {
+            if id == end {
+                Some(1)
+            } else {
+                successor_sums
+                    .iter()
+                    .try_fold(0usize, |total, pair| total.checked_add(pair.1))
+            }
+        })
+    }
+
+    // the steps of a shortest non-empty path from start to end, each as the weight of the edge taken
+    // and the node it leads to
+    pub fn shortest_path(&self, start: N, end: N) -> Result<Option<Vec<(W, N)>>, GraphError<N>> {
+        let (start, end) = (self.id(start)?, self.id(end)?);
+        let mut previous: Vec<Option<(usize, W)>> = vec![None; self.nodes.len()];
+        let mut queue = VecDeque::from([start]);
+        while let Some(id) = queue.pop_front() {
+            for &(other, weight) in self.edges[id].iter() {
+                if previous[other].is_none() {
+                    previous[other] = Some((id, weight));
+                    queue.push_back(other);
+                }
+            }
+            if previous[end].is_some() {
+                break;
+            }
+        }
+        let mut steps = Vec::new();
+        let mut id = end;
+        while let Some((from, weight)) = previous[id] {
+            steps.push((weight, self.nodes[id]));
+            if from == start {
+                steps.reverse();
+                return Ok(Some(steps));
+            }
+            id = from;
+        }
+        Ok(None)
+    }
+
+    fn id(&self, node: N) -> Result<usize, GraphError<N>> {
+        self.ids
+            .get(&node)
+            .copied()
+            .ok_or(GraphError::UnknownNode(node))
+    }
+
+    fn search(&self, edges: &[Vec<(usize, W)>], start: N) -> Result<HashSet<N>, GraphError<N>> {
+        let mut found = HashSet::new();
+        let mut stack = vec![self.id(start)?];
+        while let Some(id) = stack.pop() {
+            for &(other, _) in edges[id].iter() {
+                if found.insert(self.nodes[other]) {
+                    stack.push(other);
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    // depth first from root, skipping and marking done nodes, so successors come first
+    fn postorder(&self, root: usize, done: &mut [bool]) -> Result<Vec<usize>, GraphError<N>> {
+        let mut order = Vec::new();
+        if done[root] {
+            return Ok(order);
+        }
+        let mut on_stack = HashSet::new();
+        let mut stack = vec![(root, 0)];
+        on_stack.insert(root);
+        while let Some((id, next_edge)) = stack.pop() {
+            match self.edges[id].get(next_edge) {
+                Some(&(other, _)) => {
+                    stack.push((id, next_edge + 1));
+                    if on_stack.contains(&other) {
+                        let cycle_start = stack.iter().position(|pair| pair.0 == other).unwrap();
+                        let cycle = stack[cycle_start..].iter().map(|pair| self.nodes[pair.0]);
+                        return Err(GraphError::Cycle(cycle.collect()));
+                    }
+                    if !done[other] {
+                        on_stack.insert(other);
+                        stack.push((other, 0));
+                    }
+                }
+                None => {
+                    on_stack.remove(&id);
+                    done[id] = true;
+                    order.push(id);
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    // memoised over the nodes reachable from start, combining each node's successors' sums
+    fn path_sum<F>(&self, start: N, combine: F) -> Result<usize, GraphError<N>>
+    where
+        F: Fn(usize, &[(W, usize)]) -> Option<usize>,
+    {
+        let start = self.id(start)?;
+        let mut sums = vec![0; self.nodes.len()];
+        for id in self.postorder(start, &mut vec![false; self.nodes.len()])? {
+            let successor_sums: Vec<_> = self.edges[id]
+                .iter()
+                .map(|&(other, weight)| (weight, sums[other]))
+                .collect();
+            sums[id] = combine(id, &successor_sums).ok_or(GraphError::Overflow)?;
+        }
+        Ok(sums[start])
+    }
+}
+
+impl<N> Graph<N, usize>
+where
+    N: Copy + Eq + Hash,
+{
+    // the sum over every non-empty path from start of the product of its edge weights
+    pub fn path_weight_sum(&self, start: N) -> Result<usize, GraphError<N>> {
+        self.path_sum(start, |_, successor_sums| {
+            successor_sums
+                .iter()
+                .try_fold(0usize, |total, &(weight, inner)| {
+                    inner
+                        .checked_add(1)
+                        .and_then(|paths| paths.checked_mul(weight))
+                        .and_then(|paths| total.checked_add(paths))
+                })
+        })
+    }
+}
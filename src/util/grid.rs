@@ -0,0 +1,267 @@
+use std::ops::{Index, IndexMut};
+
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+// one of the eight symmetries of a square: an optional mirror in x followed by some number of
+// clockwise quarter turns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transform {
+    pub flip: bool,
+    pub quarter_turns: u8,
+}
+
+impl Transform {
+    pub fn all() -> impl Iterator<Item = Self> {
+        [false, true].iter().flat_map(|&flip| {
+            (0..4).map(move |quarter_turns| Self {
+                flip,
+                quarter_turns,
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid2D<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid2D<T> {
+    pub fn from_fn<F>(width: usize, height: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize, usize) -> T,
+    {
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(f(x, y));
+            }
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    // each line is a row, with cells read left to right by parse_cell
+    pub fn parse<F>(text: &str, mut parse_cell: F) -> Result<Self, String>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut cells = Vec::new();
+        let mut width = None;
+        let mut height = 0;
+        for (y, line) in text.lines().filter(|line| !line.is_empty()).enumerate() {
+            let mut row_width = 0;
+            for (x, c) in line.chars().enumerate() {
+                let cell = parse_cell(c).ok_or_else(|| {
+                    format!(
+                        "unexpected character {:?} at line {} column {}",
+                        c,
+                        y + 1,
+                        x + 1
+                    )
+                })?;
+                cells.push(cell);
+                row_width += 1;
+            }
+            match width {
+                None => width = Some(row_width),
+                Some(width) if width != row_width => {
+                    return Err(format!(
+                        "line {} has {} cells, expected {}",
+                        y + 1,
+                        row_width,
+                        width
+                    ))
+                }
+                _ => (),
+            }
+            height += 1;
+        }
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+        })
+    }
+
+    pub fn render<F>(&self, mut render_cell: F) -> String
+    where
+        F: FnMut(&T) -> char,
+    {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in self.cells.chunks(self.width.max(1)) {
+            text.extend(row.iter().map(&mut render_cell));
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn in_bounds(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height
+    }
+
+    pub fn get(&self, x: isize, y: isize) -> Option<&T> {
+        if self.in_bounds(x, y) {
+            Some(&self[(x as usize, y as usize)])
+        } else {
+            None
+        }
+    }
+
+    // treats the grid as repeating forever in every direction, so only an empty grid has no cell
+    pub fn get_wrapping(&self, x: isize, y: isize) -> Option<&T> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        Some(
+            &self[(
+                x.rem_euclid(self.width as isize) as usize,
+                y.rem_euclid(self.height as isize) as usize,
+            )],
+        )
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize), &mut T)> {
+        self.positions().zip(self.cells.iter_mut())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1))
+    }
+
+    // the in-bounds cells among the eight surrounding (x, y)
+    pub fn neighbours(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        DIRECTIONS.iter().filter_map(move |(dx, dy)| {
+            let (nx, ny) = (x as isize + dx, y as isize + dy);
+            if self.in_bounds(nx, ny) {
+                Some((nx as usize, ny as usize))
+            } else {
+                None
+            }
+        })
+    }
+
+    // the in-bounds cells visible from (x, y) in each of the eight directions, nearest first
+    pub fn rays(
+        &self,
+        x: usize,
+        y: usize,
+    ) -> impl Iterator<Item = impl Iterator<Item = (usize, usize)> + '_> + '_ {
+        DIRECTIONS.iter().map(move |&(dx, dy)| {
+            (1..)
+                .map(move |dist| (x as isize + dist * dx, y as isize + dist * dy))
+                .take_while(move |&(nx, ny)| self.in_bounds(nx, ny))
+                .map(|(nx, ny)| (nx as usize, ny as usize))
+        })
+    }
+
+    pub fn map<U, F>(&self, mut f: F) -> Grid2D<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid2D {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(&mut f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid2D<T> {
+    pub fn filled(width: usize, height: usize, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+        }
+    }
+
+    pub fn flipped(&self) -> Self {
+        Self::from_fn(self.width, self.height, |x, y| {
+            self[(self.width - 1 - x, y)].clone()
+        })
+    }
+
+    pub fn rotated_clockwise(&self) -> Self {
+        Self::from_fn(self.height, self.width, |x, y| {
+            self[(y, self.height - 1 - x)].clone()
+        })
+    }
+
+    pub fn transformed(&self, transform: Transform) -> Self {
+        let mut result = if transform.flip {
+            self.flipped()
+        } else {
+            self.clone()
+        };
+        for _ in 0..transform.quarter_turns % 4 {
+            result = result.rotated_clockwise();
+        }
+        result
+    }
+
+    pub fn orientations(&self) -> impl Iterator<Item = (Transform, Self)> + '_ {
+        Transform::all().map(move |transform| (transform, self.transformed(transform)))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid2D<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) out of bounds",
+            x,
+            y
+        );
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid2D<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) out of bounds",
+            x,
+            y
+        );
+        &mut self.cells[y * self.width + x]
+    }
+}
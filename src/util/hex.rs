@@ -0,0 +1,258 @@
+use super::{BoundingBox, Coord, CoordND};
+use std::{
+    collections::HashSet,
+    ops::{Add, Mul},
+};
+
+// r grows downwards, so on screen q runs east and r runs south-east (pointy-top) or south
+// (flat-top)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Axial {
+    pub q: i32,
+    pub r: i32,
+}
+
+// invariant: q + r + s == 0, which holds as long as cubes are only built from axial coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cube {
+    q: i32,
+    r: i32,
+    s: i32,
+}
+
+// a rectangular layout where every other row (pointy-top) or column (flat-top) is pushed half a
+// hex along, odd ones being the pushed ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Offset {
+    pub col: i32,
+    pub row: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    PointyTop,
+    FlatTop,
+}
+
+// counterclockwise on screen, starting from (1, 0)
+const DIRECTIONS: [Axial; 6] = [
+    Axial { q: 1, r: 0 },
+    Axial { q: 1, r: -1 },
+    Axial { q: 0, r: -1 },
+    Axial { q: -1, r: 0 },
+    Axial { q: -1, r: 1 },
+    Axial { q: 0, r: 1 },
+];
+
+impl From<Axial> for Cube {
+    fn from(axial: Axial) -> Self {
+        Self {
+            q: axial.q,
+            r: axial.r,
+            s: -axial.q - axial.r,
+        }
+    }
+}
+
+impl From<Cube> for Axial {
+    fn from(cube: Cube) -> Self {
+        Self {
+            q: cube.q,
+            r: cube.r,
+        }
+    }
+}
+
+impl Add for Cube {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            q: self.q + other.q,
+            r: self.r + other.r,
+            s: self.s + other.s,
+        }
+    }
+}
+
+impl Mul<i32> for Cube {
+    type Output = Self;
+
+    fn mul(self, factor: i32) -> Self {
+        Self {
+            q: self.q * factor,
+            r: self.r * factor,
+            s: self.s * factor,
+        }
+    }
+}
+
+impl Cube {
+    pub const ORIGIN: Self = Self { q: 0, r: 0, s: 0 };
+
+    pub fn distance(self, other: Self) -> u32 {
+        (self.q.abs_diff(other.q) + self.r.abs_diff(other.r) + self.s.abs_diff(other.s)) / 2
+    }
+
+    // a sixth of a turn about the origin
+    pub fn rotated_clockwise(self) -> Self {
+        Self {
+            q: -self.r,
+            r: -self.s,
+            s: -self.q,
+        }
+    }
+
+    // every hex at exactly radius steps from self, walking clockwise from the east corner so each
+    // hex is next to the one before
+    pub fn ring(self, radius: u32) -> Vec<Self> {
+        if radius == 0 {
+            return vec![self];
+        }
+        let radius = radius as i32;
+        let [east, south_east] = [DIRECTIONS[0], DIRECTIONS[5]].map(Self::from);
+        // from the east corner to the south-east one, which the rotations carry round the rest
+        let mut side: Vec<_> = (0..radius)
+            .map(|i| east * (radius - i) + south_east * i)
+            .collect();
+        let mut ring = Vec::with_capacity(6 * side.len());
+        for _ in 0..6 {
+            ring.extend(side.iter().map(|&hex| self + hex));
+            side.iter_mut()
+                .for_each(|hex| *hex = hex.rotated_clockwise());
+        }
+        ring
+    }
+
+    pub fn to_offset(self, orientation: Orientation) -> Offset {
+        match orientation {
+            Orientation::PointyTop => Offset {
+                col: self.q + self.r.div_euclid(2),
+                row: self.r,
+            },
+            Orientation::FlatTop => Offset {
+                col: self.q,
+                row: self.r + self.q.div_euclid(2),
+            },
+        }
+    }
+}
+
+impl Offset {
+    pub fn to_cube(self, orientation: Orientation) -> Cube {
+        let axial = match orientation {
+            Orientation::PointyTop => Axial {
+                q: self.col - self.row.div_euclid(2),
+                r: self.row,
+            },
+            Orientation::FlatTop => Axial {
+                q: self.col,
+                r: self.row - self.col.div_euclid(2),
+            },
+        };
+        axial.into()
+    }
+}
+
+impl Coord for Cube {
+    fn for_neighbours<F: FnMut(Self)>(&self, mut f: F) {
+        self.ring(1).into_iter().for_each(&mut f);
+    }
+}
+
+impl Orientation {
+    // each orientation has two directions along one axis and four diagonals, named in the same
+    // order as DIRECTIONS
+    fn direction_names(self) -> [&'static str; 6] {
+        match self {
+            Self::PointyTop => ["e", "ne", "nw", "w", "sw", "se"],
+            Self::FlatTop => ["se", "ne", "n", "nw", "sw", "s"],
+        }
+    }
+
+    pub fn direction(self, name: &str) -> Option<Cube> {
+        self.direction_names()
+            .iter()
+            .position(|&candidate| candidate == name)
+            .map(|idx| DIRECTIONS[idx].into())
+    }
+
+    // directions written without separators, such as "nwwswee", preferring two letter names
+    pub fn parse_directions(self, line: &str) -> Result<Vec<Cube>, String> {
+        let mut directions = Vec::new();
+        let mut rest = line;
+        while !rest.is_empty() {
+            let (direction, len) = [2, 1]
+                .iter()
+                .filter_map(|&len| {
+                    let name = rest.get(..len)?;
+                    Some((self.direction(name)?, len))
+                })
+                .next()
+                .ok_or_else(|| {
+                    format!(
+                        "expected one of {:?} at {:?}",
+                        self.direction_names(),
+                        &line[line.len() - rest.len()..]
+                    )
+                })?;
+            directions.push(direction);
+            rest = &rest[len..];
+        }
+        Ok(directions)
+    }
+
+    pub fn bounds(self, hexes: &HashSet<Cube>) -> Option<BoundingBox<2>> {
+        BoundingBox::around(hexes.iter().map(|hex| {
+            let offset = hex.to_offset(self);
+            CoordND([offset.col, offset.row])
+        }))
+    }
+
+    // draws the offset coordinates in frame, with two characters per hex across the screen and,
+    // for flat-top, two lines per hex down it
+    pub fn render(self, hexes: &HashSet<Cube>, frame: BoundingBox<2>) -> String {
+        let [col_min, row_min] = frame.min();
+        let [col_max, row_max] = frame.max();
+        let cell = |col: i32, row: i32| {
+            if hexes.contains(&Offset { col, row }.to_cube(self)) {
+                '#'
+            } else {
+                '.'
+            }
+        };
+        let mut lines = Vec::new();
+        match self {
+            Self::PointyTop => {
+                for row in row_min..=row_max {
+                    let indent = if row.rem_euclid(2) == 1 { " " } else { "" };
+                    let cells: Vec<_> = (col_min..=col_max)
+                        .map(|col| cell(col, row).to_string())
+                        .collect();
+                    lines.push(format!("{}{}", indent, cells.join(" ")));
+                }
+            }
+            Self::FlatTop => {
+                for half_row in 2 * row_min..=2 * row_max + 1 {
+                    let line: String = (col_min..=col_max)
+                        .map(|col| {
+                            let shifted = half_row - col.rem_euclid(2);
+                            let row = shifted.div_euclid(2);
+                            if shifted.rem_euclid(2) == 0 && frame.contains(CoordND([col, row])) {
+                                cell(col, row)
+                            } else {
+                                ' '
+                            }
+                        })
+                        .flat_map(|c| [c, ' '])
+                        .collect();
+                    lines.push(line);
+                }
+            }
+        }
+        lines
+            .iter()
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect()
+    }
+}
@@ -0,0 +1,295 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModError {
+    ZeroModulus,
+    NoInverse { value: u64, modulus: u64 },
+    NoSolution,
+    Overflow,
+}
+
+impl fmt::Display for ModError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZeroModulus => write!(f, "modulus must be non-zero"),
+            Self::NoInverse { value, modulus } => write!(
+                f,
+                "{} has no inverse modulo {} as they aren't coprime",
+                value, modulus
+            ),
+            Self::NoSolution => write!(f, "no solution exists"),
+            Self::Overflow => write!(f, "the combined modulus doesn't fit in 128 bits"),
+        }
+    }
+}
+
+pub fn mul_mod(a: u64, b: u64, modulus: u64) -> u64 {
+    ((a as u128 * b as u128) % modulus as u128) as u64
+}
+
+pub fn pow_mod(base: u64, mut exp: u64, modulus: u64) -> u64 {
+    let mut base = base % modulus;
+    let mut result = 1 % modulus;
+    while exp > 0 {
+        if exp % 2 == 1 {
+            result = mul_mod(result, base, modulus);
+        }
+        base = mul_mod(base, base, modulus);
+        exp /= 2;
+    }
+    result
+}
+
+// returns (g, x, y) with a * x + b * y == g == gcd(a, b)
+pub fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (1, 0);
+    let (mut old_y, mut y) = (0, 1);
+    while r != 0 {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < 0 {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+// merges the congruences x = r (mod m) pairwise, returning the solution modulo the lcm of the
+// moduli along with that lcm
+pub fn chinese_remainder(
+    congruences: impl IntoIterator<Item = (u64, u64)>,
+) -> Result<(u128, u128), ModError> {
+    let mut solution = 0;
+    let mut big_modulus = 1;
+    for (remainder, modulus) in congruences {
+        if modulus == 0 {
+            return Err(ModError::ZeroModulus);
+        }
+        let common = gcd(big_modulus, modulus as u128) as u64;
+        let difference = ModInt::new(remainder, modulus)
+            - ModInt::new((solution % modulus as u128) as u64, modulus);
+        if !difference.value().is_multiple_of(common) {
+            return Err(ModError::NoSolution);
+        }
+        let step_modulus = modulus / common;
+        let steps =
+            ModInt::new(difference.value() / common, step_modulus).checked_div(ModInt::new(
+                ((big_modulus / common as u128) % step_modulus as u128) as u64,
+                step_modulus,
+            ))?;
+        let next_modulus = big_modulus
+            .checked_mul(step_modulus as u128)
+            .ok_or(ModError::Overflow)?;
+        solution += big_modulus * steps.value() as u128;
+        big_modulus = next_modulus;
+    }
+    Ok((solution, big_modulus))
+}
+
+// prime factors with their multiplicities, by trial division
+pub fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p <= n / p {
+        let mut multiplicity = 0;
+        while n.is_multiple_of(p) {
+            n /= p;
+            multiplicity += 1;
+        }
+        if multiplicity > 0 {
+            factors.push((p, multiplicity));
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+// smallest x < bound with base^x = target, by baby-step giant-step
+fn discrete_log_bounded(base: u64, target: u64, modulus: u64, bound: u64) -> Result<u64, ModError> {
+    let base = ModInt::new(base, modulus);
+    let step_count = (bound as f64).sqrt().ceil() as u64 + 1;
+    let mut baby_steps = HashMap::new();
+    let mut power = ModInt::new(1, modulus);
+    for j in 0..step_count {
+        baby_steps.entry(power.value()).or_insert(j);
+        power = power * base;
+    }
+    let giant_step = base.pow(step_count).inverse()?;
+    let mut gamma = ModInt::new(target, modulus);
+    for i in 0..step_count {
+        if let Some(j) = baby_steps.get(&gamma.value()) {
+            return Ok(i * step_count + j);
+        }
+        gamma = gamma * giant_step;
+    }
+    Err(ModError::NoSolution)
+}
+
+// smallest x with base^x = target, base must be coprime to the modulus
+pub fn discrete_log(base: u64, target: u64, modulus: u64) -> Result<u64, ModError> {
+    if modulus == 0 {
+        return Err(ModError::ZeroModulus);
+    }
+    discrete_log_bounded(base, target, modulus, modulus)
+}
+
+// as discrete_log, but uses Pohlig-Hellman to split the problem over the prime powers dividing
+// the order of base, so the modulus must be prime
+pub fn discrete_log_prime(base: u64, target: u64, prime: u64) -> Result<u64, ModError> {
+    if prime == 0 {
+        return Err(ModError::ZeroModulus);
+    }
+    let base = ModInt::new(base, prime);
+    let target = ModInt::new(target, prime);
+    let group_factors = factorize(prime - 1);
+    let mut order = prime - 1;
+    for &(p, _) in group_factors.iter() {
+        while order.is_multiple_of(p) && base.pow(order / p).value() == 1 {
+            order /= p;
+        }
+    }
+    let mut congruences = Vec::new();
+    for (p, multiplicity) in factorize(order) {
+        let prime_power = p.pow(multiplicity);
+        let sub_base = base.pow(order / prime_power);
+        let sub_target = target.pow(order / prime_power);
+        let generator = sub_base.pow(prime_power / p);
+        let mut x = 0;
+        let mut p_k = 1;
+        for k in 0..multiplicity {
+            let remaining =
+                (sub_base.pow(x).inverse()? * sub_target).pow(p.pow(multiplicity - 1 - k));
+            let digit = discrete_log_bounded(generator.value(), remaining.value(), prime, p)?;
+            x += p_k * digit;
+            p_k *= p;
+        }
+        congruences.push((x, prime_power));
+    }
+    let (x, _) = chinese_remainder(congruences)?;
+    let x = x as u64;
+    if base.pow(x) == target {
+        Ok(x)
+    } else {
+        Err(ModError::NoSolution)
+    }
+}
+
+pub fn inverse_mod(value: u64, modulus: u64) -> Result<u64, ModError> {
+    if modulus == 0 {
+        return Err(ModError::ZeroModulus);
+    }
+    let value = value % modulus;
+    match extended_gcd(value as i128, modulus as i128) {
+        (1, x, _) => Ok(x.rem_euclid(modulus as i128) as u64),
+        _ if modulus == 1 => Ok(0),
+        _ => Err(ModError::NoInverse { value, modulus }),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ModInt {
+    value: u64,
+    modulus: u64,
+}
+
+impl ModInt {
+    pub fn new(value: u64, modulus: u64) -> Self {
+        assert!(modulus != 0, "modulus must be non-zero");
+        Self {
+            value: value % modulus,
+            modulus,
+        }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn pow(self, exp: u64) -> Self {
+        Self {
+            value: pow_mod(self.value, exp, self.modulus),
+            ..self
+        }
+    }
+
+    pub fn inverse(self) -> Result<Self, ModError> {
+        Ok(Self {
+            value: inverse_mod(self.value, self.modulus)?,
+            ..self
+        })
+    }
+
+    pub fn checked_div(self, other: Self) -> Result<Self, ModError> {
+        Ok(self * other.inverse()?)
+    }
+
+    fn with_value(self, other: Self, value: u64) -> Self {
+        assert!(
+            self.modulus == other.modulus,
+            "mismatched moduli {} and {}",
+            self.modulus,
+            other.modulus
+        );
+        Self { value, ..self }
+    }
+}
+
+impl Add for ModInt {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let sum = (self.value as u128 + other.value as u128) % self.modulus as u128;
+        self.with_value(other, sum as u64)
+    }
+}
+
+impl Sub for ModInt {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Neg for ModInt {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            value: (self.modulus - self.value) % self.modulus,
+            ..self
+        }
+    }
+}
+
+impl Mul for ModInt {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.with_value(other, mul_mod(self.value, other.value, self.modulus))
+    }
+}
+
+impl fmt::Display for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (mod {})", self.value, self.modulus)
+    }
+}
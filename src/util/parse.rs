@@ -0,0 +1,317 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {} column {}: expected {}, found {:?}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+// the unparsed text along with where it starts in the original source
+#[derive(Debug, Clone, Copy)]
+pub struct Input<'a> {
+    rest: &'a str,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            rest: source,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn advance(mut self, len: usize) -> Self {
+        for c in self.rest[..len].chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.rest = &self.rest[len..];
+        self
+    }
+
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        let found = self.rest.lines().next().unwrap_or("");
+        ParseError {
+            line: self.line,
+            column: self.column,
+            expected: expected.into(),
+            found: found.chars().take(20).collect(),
+        }
+    }
+
+    // runs the parser on just the first len bytes, requiring it to consume all of them
+    fn parse_within<T>(self, len: usize, parser: &impl Parser<'a, T>) -> Result<T, ParseError> {
+        let restricted = Self {
+            rest: &self.rest[..len],
+            ..self
+        };
+        let (value, rest) = parser.parse(restricted)?;
+        if rest.rest.is_empty() {
+            Ok(value)
+        } else {
+            Err(rest.error("end of input"))
+        }
+    }
+}
+
+pub type ParseResult<'a, T> = Result<(T, Input<'a>), ParseError>;
+
+pub trait Parser<'a, T> {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T>;
+}
+
+impl<'a, T, F> Parser<'a, T> for F
+where
+    F: Fn(Input<'a>) -> ParseResult<'a, T>,
+{
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T> {
+        self(input)
+    }
+}
+
+pub fn parse_all<'a, T>(parser: impl Parser<'a, T>, source: &'a str) -> Result<T, ParseError> {
+    Input::new(source).parse_within(source.len(), &parser)
+}
+
+pub fn literal<'a>(expected: &'static str) -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        if input.rest.starts_with(expected) {
+            Ok((&input.rest[..expected.len()], input.advance(expected.len())))
+        } else {
+            Err(input.error(format!("{:?}", expected)))
+        }
+    }
+}
+
+pub fn one_of<'a>(options: &'static [&'static str]) -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        options
+            .iter()
+            .find(|option| input.rest.starts_with(*option))
+            .map(|option| (&input.rest[..option.len()], input.advance(option.len())))
+            .ok_or_else(|| input.error(format!("one of {:?}", options)))
+    }
+}
+
+pub fn take_while1<'a, P>(predicate: P, description: &'static str) -> impl Parser<'a, &'a str>
+where
+    P: Fn(char) -> bool,
+{
+    move |input: Input<'a>| {
+        let len = input
+            .rest
+            .find(|c| !predicate(c))
+            .unwrap_or(input.rest.len());
+        if len == 0 {
+            Err(input.error(description))
+        } else {
+            Ok((&input.rest[..len], input.advance(len)))
+        }
+    }
+}
+
+pub fn word<'a>() -> impl Parser<'a, &'a str> {
+    take_while1(char::is_alphanumeric, "a word")
+}
+
+pub fn line_ending<'a>() -> impl Parser<'a, &'a str> {
+    either(literal("\r\n"), literal("\n"))
+}
+
+pub fn rest<'a>() -> impl Parser<'a, &'a str> {
+    |input: Input<'a>| Ok((input.rest, input.advance(input.rest.len())))
+}
+
+pub fn integer<'a, T: FromStr>() -> impl Parser<'a, T> {
+    move |input: Input<'a>| {
+        let sign_len = if input.rest.starts_with(&['+', '-'][..]) {
+            1
+        } else {
+            0
+        };
+        let digits_len = input.rest[sign_len..]
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(input.rest.len() - sign_len);
+        let len = sign_len + digits_len;
+        match input.rest[..len].parse() {
+            Ok(value) if digits_len > 0 => Ok((value, input.advance(len))),
+            _ => Err(input.error("an integer")),
+        }
+    }
+}
+
+pub fn map<'a, A, B, F>(parser: impl Parser<'a, A>, f: F) -> impl Parser<'a, B>
+where
+    F: Fn(A) -> B,
+{
+    move |input| parser.parse(input).map(|(value, rest)| (f(value), rest))
+}
+
+// the slice of source text consumed by the parser
+pub fn recognize<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        let (_, rest) = parser.parse(input)?;
+        let len = input.rest.len() - rest.rest.len();
+        Ok((&input.rest[..len], rest))
+    }
+}
+
+pub fn pair<'a, A, B>(
+    first: impl Parser<'a, A>,
+    second: impl Parser<'a, B>,
+) -> impl Parser<'a, (A, B)> {
+    move |input| {
+        let (a, input) = first.parse(input)?;
+        let (b, input) = second.parse(input)?;
+        Ok(((a, b), input))
+    }
+}
+
+pub fn preceded<'a, A, B>(
+    skipped: impl Parser<'a, A>,
+    kept: impl Parser<'a, B>,
+) -> impl Parser<'a, B> {
+    map(pair(skipped, kept), |pair| pair.1)
+}
+
+pub fn terminated<'a, A, B>(
+    kept: impl Parser<'a, A>,
+    skipped: impl Parser<'a, B>,
+) -> impl Parser<'a, A> {
+    map(pair(kept, skipped), |pair| pair.0)
+}
+
+pub fn delimited<'a, A, B, C>(
+    open: impl Parser<'a, A>,
+    kept: impl Parser<'a, B>,
+    close: impl Parser<'a, C>,
+) -> impl Parser<'a, B> {
+    preceded(open, terminated(kept, close))
+}
+
+// tries first, then second from the same place, reporting whichever got further on failure
+pub fn either<'a, T>(first: impl Parser<'a, T>, second: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    move |input| {
+        first.parse(input).or_else(|first_err| {
+            second.parse(input).map_err(|second_err| {
+                let first_pos = (first_err.line, first_err.column);
+                let second_pos = (second_err.line, second_err.column);
+                if first_pos == second_pos {
+                    ParseError {
+                        expected: format!("{} or {}", first_err.expected, second_err.expected),
+                        ..first_err
+                    }
+                } else if first_pos > second_pos {
+                    first_err
+                } else {
+                    second_err
+                }
+            })
+        })
+    }
+}
+
+pub fn optional<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, Option<T>> {
+    move |input| match parser.parse(input) {
+        Ok((value, rest)) => Ok((Some(value), rest)),
+        Err(_) => Ok((None, input)),
+    }
+}
+
+// one or more items, where a separator is only consumed if an item follows it
+pub fn separated<'a, T, S>(
+    item: impl Parser<'a, T>,
+    separator: impl Parser<'a, S>,
+) -> impl Parser<'a, Vec<T>> {
+    move |input| {
+        let (first, mut input) = item.parse(input)?;
+        let mut items = vec![first];
+        while let Ok((_, after_separator)) = separator.parse(input) {
+            match item.parse(after_separator) {
+                Ok((next, rest)) => {
+                    items.push(next);
+                    input = rest;
+                }
+                Err(_) => break,
+            }
+        }
+        Ok((items, input))
+    }
+}
+
+// parses every non-blank line with the given parser, which must consume the whole line
+pub fn lines<'a, T>(line: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |mut input: Input<'a>| {
+        let mut items = Vec::new();
+        while !input.rest.is_empty() {
+            let line_len = input.rest.find('\n').unwrap_or(input.rest.len());
+            let content = input.rest[..line_len].trim_end_matches('\r');
+            if !content.trim().is_empty() {
+                items.push(input.parse_within(content.len(), &line)?);
+            }
+            input = input.advance((line_len + 1).min(input.rest.len()));
+        }
+        Ok((items, input))
+    }
+}
+
+// parses each group of lines separated by blank lines with the given parser, which must consume
+// the whole group apart from its final line ending
+pub fn blocks<'a, T>(block: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |mut input: Input<'a>| {
+        let mut items = Vec::new();
+        loop {
+            let blank_len =
+                input
+                    .rest
+                    .find(|c: char| !c.is_whitespace())
+                    .map_or(input.rest.len(), |first| {
+                        input.rest[..first]
+                            .rfind('\n')
+                            .map_or(0, |newline| newline + 1)
+                    });
+            input = input.advance(blank_len);
+            if input.rest.is_empty() {
+                break;
+            }
+            let mut block_len = 0;
+            for line in input.rest.split_inclusive('\n') {
+                if line.trim().is_empty() {
+                    break;
+                }
+                block_len += line.len();
+            }
+            let content = input.rest[..block_len].trim_end_matches(&['\r', '\n'][..]);
+            items.push(input.parse_within(content.len(), &block)?);
+            input = input.advance(block_len);
+        }
+        Ok((items, input))
+    }
+}
@@ -0,0 +1,197 @@
+use std::{fmt, str::FromStr};
+
+// a single character, ".", "\d", "\w", an escaped character such as "\." or a bracketed set such
+// as "[a-cx]" or "[^aeiou]"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharClass {
+    source: String,
+    ranges: Vec<(char, char)>,
+    negated: bool,
+}
+
+impl CharClass {
+    pub fn matches(&self, c: char) -> bool {
+        let in_ranges = self.ranges.iter().any(|&(low, high)| low <= c && c <= high);
+        in_ranges != self.negated
+    }
+
+    // the class at the start of chars, along with how many chars it takes up
+    fn parse_prefix(chars: &[char]) -> Result<(Self, usize), String> {
+        let class = |len: usize, ranges: Vec<(char, char)>, negated| {
+            let class = Self {
+                source: chars[..len].iter().collect(),
+                ranges,
+                negated,
+            };
+            Ok((class, len))
+        };
+        match chars {
+            ['.', ..] => class(1, Vec::new(), true),
+            ['\\', 'd', ..] => class(2, vec![('0', '9')], false),
+            ['\\', 'w', ..] => class(
+                2,
+                vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')],
+                false,
+            ),
+            ['\\', c, ..] => class(2, vec![(*c, *c)], false),
+            ['[', rest @ ..] => {
+                let close = rest.iter().position(|&c| c == ']').ok_or_else(|| {
+                    format!(
+                        "unclosed character class {:?}",
+                        chars.iter().collect::<String>()
+                    )
+                })?;
+                let (negated, mut set) = match &rest[..close] {
+                    ['^', set @ ..] => (true, set),
+                    set => (false, set),
+                };
+                let mut ranges = Vec::new();
+                while !set.is_empty() {
+                    match set {
+                        [low, '-', high, rest @ ..] if low <= high => {
+                            ranges.push((*low, *high));
+                            set = rest;
+                        }
+                        [c, rest @ ..] => {
+                            ranges.push((*c, *c));
+                            set = rest;
+                        }
+                        [] => unreachable!(),
+                    }
+                }
+                if ranges.is_empty() {
+                    let source: String = chars[..close + 2].iter().collect();
+                    Err(format!("empty character class {:?}", source))
+                } else {
+                    class(close + 2, ranges, negated)
+                }
+            }
+            [c, ..] if !"]{}?*+".contains(*c) => class(1, vec![(*c, *c)], false),
+            _ => Err(format!(
+                "{:?} doesn't start with a character class",
+                chars.iter().collect::<String>()
+            )),
+        }
+    }
+}
+
+impl FromStr for CharClass {
+    type Err = String;
+
+    // on its own, any character other than "." stands for itself, even ones that are special in a
+    // pattern
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = source.chars().collect();
+        if let [c] = chars[..] {
+            if c != '.' {
+                return Ok(Self {
+                    source: source.to_string(),
+                    ranges: vec![(c, c)],
+                    negated: false,
+                });
+            }
+        }
+        match Self::parse_prefix(&chars)? {
+            (class, len) if len == chars.len() => Ok(class),
+            _ => Err(format!("invalid character class {:?}", source)),
+        }
+    }
+}
+
+impl fmt::Display for CharClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}
+
+// character classes, each optionally followed by "?", "*", "+", "{n}", "{n,}" or "{n,m}", which
+// must match the whole of a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    source: String,
+    items: Vec<(CharClass, usize, Option<usize>)>,
+}
+
+impl Pattern {
+    pub fn matches(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        Self::matches_from(&self.items, &chars)
+    }
+
+    // takes as many characters as possible for the first item, backtracking when the rest fail
+    fn matches_from(items: &[(CharClass, usize, Option<usize>)], chars: &[char]) -> bool {
+        match items {
+            [] => chars.is_empty(),
+            [(class, min, max), rest @ ..] => {
+                let limit = max.unwrap_or(chars.len()).min(chars.len());
+                let available = chars[..limit]
+                    .iter()
+                    .position(|&c| !class.matches(c))
+                    .unwrap_or(limit);
+                (*min..=available)
+                    .rev()
+                    .any(|taken| Self::matches_from(rest, &chars[taken..]))
+            }
+        }
+    }
+}
+
+fn parse_repetition(chars: &[char]) -> Result<(usize, Option<usize>, usize), String> {
+    let invalid = || {
+        format!(
+            "invalid repetition {:?}, expected e.g. {{3}} or {{1,4}}",
+            chars.iter().collect::<String>()
+        )
+    };
+    match chars {
+        ['?', ..] => Ok((0, Some(1), 1)),
+        ['*', ..] => Ok((0, None, 1)),
+        ['+', ..] => Ok((1, None, 1)),
+        ['{', rest @ ..] => {
+            let close = rest.iter().position(|&c| c == '}').ok_or_else(invalid)?;
+            let inside: String = rest[..close].iter().collect();
+            let (min, max) = match inside.split_once(',') {
+                None => {
+                    let count = inside.parse().map_err(|_| invalid())?;
+                    (count, Some(count))
+                }
+                Some((min, "")) => (min.parse().map_err(|_| invalid())?, None),
+                Some((min, max)) => (
+                    min.parse().map_err(|_| invalid())?,
+                    Some(max.parse().map_err(|_| invalid())?),
+                ),
+            };
+            match max {
+                Some(max) if max < min => Err(invalid()),
+                _ => Ok((min, max, close + 2)),
+            }
+        }
+        _ => Ok((1, Some(1), 0)),
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = source.chars().collect();
+        let mut rest = chars.as_slice();
+        let mut items = Vec::new();
+        while !rest.is_empty() {
+            let (class, class_len) = CharClass::parse_prefix(rest)?;
+            let (min, max, repetition_len) = parse_repetition(&rest[class_len..])?;
+            items.push((class, min, max));
+            rest = &rest[class_len + repetition_len..];
+        }
+        Ok(Self {
+            source: source.to_string(),
+            items,
+        })
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.source)
+    }
+}